@@ -0,0 +1,54 @@
+//! Compares two netvar dumps written by `sdk::netvar::dump::NetvarDump`.
+//!
+//! Usage: `netvar_diff <old dump> <new dump>`
+//!
+//! Prints every added, removed, moved or retyped prop grouped by table, then the
+//! `netvar!` accessors reading any of them, and exits with `1` if anything changed.
+
+use std::{env, fs, process};
+
+use sdk::netvar::diff::diff;
+use sdk::netvar::dump::NetvarDump;
+
+fn load(path: &str) -> NetvarDump {
+    let text = fs::read_to_string(path).unwrap_or_else(|error| {
+        eprintln!("failed to read {}: {}", path, error);
+        process::exit(2);
+    });
+
+    NetvarDump::parse(&text).unwrap_or_else(|error| {
+        eprintln!("failed to parse {}: {}", path, error);
+        process::exit(2);
+    })
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 3 {
+        eprintln!("usage: {} <old dump> <new dump>", args[0]);
+        process::exit(2);
+    }
+
+    let changes = diff(&load(&args[1]), &load(&args[2]));
+
+    if changes.is_empty() {
+        println!("no changes");
+        return;
+    }
+
+    print!("{}", changes);
+
+    let affected = changes.affected();
+
+    if !affected.is_empty() {
+        println!();
+        println!("affected accessors");
+
+        for (declaration, change) in affected {
+            println!("  {}: {}", declaration, change);
+        }
+    }
+
+    process::exit(1);
+}
@@ -0,0 +1,57 @@
+//! Generates the shortest unique signature for an address in a module file.
+//!
+//! Usage: `sig_gen <module file> <address>`
+//!
+//! The address can be hexadecimal with `0x` or decimal, and either a virtual address or an
+//! RVA. Prints the signature in the format `Signature::parse` reads and exits with `1` if
+//! there is no unique one.
+
+use std::{env, fs, process};
+
+use sdk::utils::image::Image;
+use sdk::utils::signature_gen::Code;
+
+fn parse_address(text: &str) -> Option<usize> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 3 {
+        eprintln!("usage: {} <module file> <address>", args[0]);
+        process::exit(2);
+    }
+
+    let file = fs::read(&args[1]).unwrap_or_else(|error| {
+        eprintln!("failed to read {}: {}", args[1], error);
+        process::exit(2);
+    });
+
+    let image = Image::parse(&file).unwrap_or_else(|error| {
+        eprintln!("failed to parse {}: {}", args[1], error);
+        process::exit(2);
+    });
+
+    let address = parse_address(&args[2]).unwrap_or_else(|| {
+        eprintln!("invalid address {}", args[2]);
+        process::exit(2);
+    });
+
+    // Anything below the preferred image base can only be an RVA.
+    let rva = match address.checked_sub(image.image_base) {
+        Some(rva) if image.image_base > 0 => rva,
+        _ => address,
+    };
+
+    match Code::from_image(&image).generate(rva) {
+        Ok(signature) => println!("{}", signature),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+}
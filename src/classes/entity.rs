@@ -2,22 +2,24 @@ use core::mem::MaybeUninit;
 use core::ptr::null_mut;
 
 use libc::{c_char, c_void};
-use static_init::dynamic;
 
 use crate::classes::utl_vector::CUtlVec;
 use crate::classes::weapon::CWeapon;
 use crate::classes::Entity;
 use crate::definitions::bones;
+use crate::definitions::datamap::DataMap;
 use crate::interfaces::client::ClientClass;
 use crate::interfaces::engine_trace::{Ray, Trace, TraceFilterGeneric, TraceFilterTrait};
+use crate::netvar::classes::NetworkClass;
+use crate::netvar::datamap::DataFieldDecl;
+use crate::netvar::{classes, CachedOffset};
 use crate::utils::math::matrix::Matrix3x4T;
 use crate::utils::math::vector::{Vec2, Vec3};
-use crate::utils::memory::{pattern_scan, read};
-use crate::utils::platform::modules;
+use crate::utils::memory;
 use crate::utils::string::StringExt;
-use crate::utils::{memory, patterns};
-use crate::{get_entity_by_id, get_interfaces, get_local_player, netvar};
+use crate::{get_entity_by_id, get_interfaces, get_local_player};
 use alloc::string::String;
+use alloc::vec::Vec;
 use num_traits::One;
 
 type CBaseHandle = usize;
@@ -115,6 +117,8 @@ interface!(
     CEntity,
     setup_bone_matrix[0](out: &mut Matrix3x4T) -> bool,
     pub get_abs_origin[10]() -> &'static Vec3,
+    pub get_data_desc_map[15]() -> *const DataMap,
+    pub get_pred_desc_map[17]() -> *const DataMap,
     pub get_team[88]() -> i32,
     pub is_alive[156]() -> bool,
     pub is_player[158]() -> bool,
@@ -126,68 +130,176 @@ impl ICollideable<CEntity> for CEntity {}
 
 impl IClientNetworkable<CEntity> for CEntity {}
 
-impl Entity for CEntity {}
+impl Entity for CEntity {
+    /// Every networked class is an entity.
+    fn matches(_class: &NetworkClass) -> bool {
+        true
+    }
+}
 
 impl CEntity {
-    netvar!("DT_BasePlayer", "m_iHealth", get_health, i32);
-    netvar!("DT_CSPlayer", "m_ArmorValue", get_armor, i32);
-    netvar!("DT_CSPlayer", "m_bIsScoped", is_scoped, bool);
-    netvar!("DT_CSPlayer", "m_bIsDefusing", is_defusing, bool);
-    netvar!("DT_CSPlayer", "m_bHasDefuser", 0x5c, get_crosshair_id, i32);
-    netvar!("DT_BasePlayer", "m_fFlags", get_flags, i32);
-    netvar!(
-        "DT_CSPlayer",
-        "m_flFlashDuration",
-        get_flash_duration,
-        set_flash_duration,
-        i32
-    );
-    netvar!("DT_BaseEntity", "m_bSpotted", is_spotted, set_spotted, bool);
-    netvar!("DT_BaseEntity", "m_vecOrigin", get_origin, Vec3);
-    netvar!("DT_BasePlayer", "m_vecViewOffset", get_view_offset, Vec3);
-    netvar!("DT_BasePlayer", "m_vecVelocity", get_velocity, Vec3);
-    netvar!(
-        "DT_BasePlayer",
-        "m_hViewModel[0]",
-        get_view_model,
-        set_view_model,
-        i32
-    );
-    netvar!(
-        "DT_BaseEntity",
-        "m_Collision",
-        0x14,
-        get_collideable_max,
-        Vec3
-    );
-    netvar!(
-        "DT_CSPlayer",
-        "m_flFlashDuration",
-        0x18,
-        get_glow_index,
-        usize
-    );
-    netvar!("DT_BasePlayer", "m_iObserverMode", get_observer_mode, i32);
-    netvar!(
-        "DT_BaseCombatWeapon",
-        "m_flNextPrimaryAttack",
-        get_weapon_cooldown,
-        i32
-    );
-    netvar!(
-        "DT_BaseCombatCharacter",
-        "m_hActiveWeapon",
-        get_active_weapon,
-        i32
-    );
-    netvar!("DT_BasePlayer", "m_nTickBase", get_tickbase, i32);
-    netvar!("DT_BaseCombatCharacter", "m_flNextAttack", next_attack, f32);
-    netvar!("DT_CSPlayer", "m_iShotsFired", shots_fired, i32);
-    netvar!("DT_BasePlayer", "m_aimPunchAngle", get_aim_punch, Vec2);
-    netvar!("DT_BasePlayer", "m_lifeState", get_life_state, i32);
-    netvar!("DT_CSPlayer", "m_bGunGameImmunity", is_immune, bool);
-    netvar!("DT_CSPlayer", "m_bHasHelmet", has_helmet, bool);
-    netvar!("DT_CSPlayer", "m_iAccount", get_money, i32);
+    /// Returns the networked class of the entity, e.g. `CCSPlayer`.
+    pub fn get_network_class(&self) -> Option<NetworkClass> {
+        if self.is_null() {
+            return None;
+        }
+
+        let class = self.get_client_class();
+        let class_id = unsafe { class.as_ref() }?.class_id;
+
+        classes::get(class_id).or_else(|| {
+            unsafe { classes::register(class) };
+            classes::get(class_id)
+        })
+    }
+
+    /// Returns the entity as `T` if its runtime class matches `T`.
+    /// # Examples
+    /// ```no_run
+    /// use sdk::classes::weapon::CWeapon;
+    ///
+    /// if let Some(weapon) = sdk::get_entity_by_id(70).and_then(|entity| entity.downcast::<CWeapon>()) {
+    ///     println!("{}", weapon.get_inaccuracy());
+    /// }
+    /// ```
+    pub fn downcast<T: Entity>(&self) -> Option<T> {
+        if self.is_null() || !classes::matches(self.get_client_class(), T::matches) {
+            return None;
+        }
+
+        Some(unsafe { T::from_raw_unchecked(self.as_ptr()) })
+    }
+
+    netvars! {
+        CEntity;
+        ("DT_BasePlayer", "m_iHealth", get_health, i32),
+        ("DT_CSPlayer", "m_ArmorValue", get_armor, i32),
+        ("DT_CSPlayer", "m_bIsScoped", is_scoped, bool),
+        ("DT_CSPlayer", "m_bIsDefusing", is_defusing, bool),
+        ("DT_BasePlayer", "m_fFlags", get_flags, i32),
+        (
+            "DT_CSPlayer",
+            "m_flFlashDuration",
+            get_flash_duration,
+            set_flash_duration,
+            f32
+        ),
+        ("DT_BaseEntity", "m_bSpotted", is_spotted, set_spotted, bool),
+        ("DT_BaseEntity", "m_vecOrigin", get_origin, Vec3),
+        ("DT_BasePlayer", "m_vecViewOffset", get_view_offset, Vec3),
+        ("DT_BasePlayer", "m_vecVelocity", get_velocity, Vec3),
+        ("DT_BasePlayer", "m_hViewModel", get_view_models, [i32]),
+        (
+            "DT_BaseCombatCharacter",
+            "m_hMyWeapons",
+            get_weapons,
+            [u32]
+        ),
+        (
+            "DT_BaseEntity",
+            "m_Collision",
+            0x14,
+            get_collideable_max,
+            Vec3
+        ),
+        ("DT_BasePlayer", "m_iObserverMode", get_observer_mode, i32),
+        (
+            "DT_BaseCombatWeapon",
+            "m_flNextPrimaryAttack",
+            get_weapon_cooldown,
+            f32
+        ),
+        (
+            "DT_BaseCombatCharacter",
+            "m_hActiveWeapon",
+            get_active_weapon,
+            i32
+        ),
+        ("DT_BasePlayer", "m_nTickBase", get_tickbase, i32),
+        ("DT_BaseCombatCharacter", "m_flNextAttack", next_attack, f32),
+        ("DT_CSPlayer", "m_iShotsFired", shots_fired, i32),
+        ("DT_BasePlayer", "m_aimPunchAngle", get_aim_punch, Vec2),
+        ("DT_BasePlayer", "m_lifeState", get_life_state, i32),
+        ("DT_CSPlayer", "m_bGunGameImmunity", is_immune, bool),
+        ("DT_CSPlayer", "m_bHasHelmet", has_helmet, bool),
+        ("DT_CSPlayer", "m_iAccount", get_money, i32),
+    }
+
+    /// Returns the handle of the first view model.
+    #[deprecated(note = "use `get_view_models().get(0)` instead")]
+    pub fn get_view_model(&self) -> i32 {
+        self.get_view_models().get(0).unwrap_or_default()
+    }
+
+    /// Sets the handle of the first view model.
+    #[deprecated(note = "use `get_view_models().set(0, handle)` instead")]
+    pub fn set_view_model(&self, state: i32) {
+        let _ = self.get_view_models().set(0, state);
+    }
+
+    /// Returns the index of the entity under the crosshair.
+    pub fn get_crosshair_id(&self) -> i32 {
+        static OFFSET: CachedOffset = CachedOffset::new();
+
+        if self.is_null() {
+            return 0;
+        }
+
+        let offset = some_or_ret!(OFFSET.get(|| self.data_field_offset("get_crosshair_id")), 0);
+
+        self.get_value(offset)
+    }
+
+    /// Returns the entity's slot in the glow object manager.
+    pub fn get_glow_index(&self) -> usize {
+        static OFFSET: CachedOffset = CachedOffset::new();
+
+        if self.is_null() {
+            return 0;
+        }
+
+        let offset = some_or_ret!(OFFSET.get(|| self.data_field_offset("get_glow_index")), 0);
+
+        self.get_value(offset)
+    }
+
+    /// Returns every non-networked field read by the accessors of this type.
+    ///
+    /// `sdk::initialize` lists the fields that aren't found in the datamaps in its report.
+    pub(crate) fn data_fields() -> Vec<DataFieldDecl> {
+        vec![
+            DataFieldDecl {
+                accessor: "get_crosshair_id",
+                field: Some(obfstr!("m_iIDEntIndex").into()),
+                table: obfstr!("DT_CSPlayer").into(),
+                netvar: obfstr!("m_bHasDefuser").into(),
+                delta: 0x5C,
+            },
+            DataFieldDecl {
+                accessor: "get_glow_index",
+                field: Some(obfstr!("m_iGlowIndex").into()),
+                table: obfstr!("DT_CSPlayer").into(),
+                netvar: obfstr!("m_flFlashDuration").into(),
+                delta: 0x18,
+            },
+            // `m_BoneAccessor` is a plain member of `C_BaseAnimating`, no datamap describes it.
+            DataFieldDecl {
+                accessor: "get_bone_pos",
+                field: None,
+                table: obfstr!("DT_BaseAnimating").into(),
+                netvar: obfstr!("m_nForceBone").into(),
+                delta: 0x1C,
+            },
+        ]
+    }
+
+    /// Looks up the offset of the field `accessor` reads, see [`CEntity::data_fields`].
+    fn data_field_offset(&self, accessor: &str) -> Option<usize> {
+        Self::data_fields()
+            .iter()
+            .find(|declaration| declaration.accessor == accessor)?
+            .resolve(self)
+    }
 
     pub fn is_valid_player(&self) -> bool {
         self.is_player()
@@ -206,27 +318,15 @@ impl CEntity {
     }
 
     pub fn is_other_enemy(&self, other: &CEntity) -> bool {
-        #[dynamic]
-        static FN_PTR: Option<extern "thiscall" fn(*const usize, *const usize) -> bool> = {
-            let address = pattern_scan(modules::CLIENT, patterns::IS_OTHER_ENEMY)? as usize + 0x3;
-
-            if (address as *const usize).is_null() {
-                return None;
-            }
-
-            unsafe {
-                Some(transmute!(
-                    address + 0x4 + read::<usize>(address),
-                    extern "thiscall" fn(*const usize, *const usize) -> bool
-                ))
-            }
+        let address = some_or_ret!(get_interfaces().is_other_enemy, false);
+        let function = unsafe {
+            transmute!(
+                address,
+                extern "thiscall" fn(*const usize, *const usize) -> bool
+            )
         };
 
-        if let Some(function) = *FN_PTR {
-            return function(self.base as _, other.base as _);
-        }
-
-        false
+        function(self.base as _, other.base as _)
     }
 
     pub fn is_audible(&self) -> bool {
@@ -257,13 +357,6 @@ impl CEntity {
         some_or_ret!(self.get_weapon(), false).can_fire()
     }
 
-    pub fn get_weapons(&self) -> [u32; 48] {
-        self.get_value::<[u32; 48]>(netvar::get_offset(
-            obfstr!("DT_BaseCombatCharacter"),
-            obfstr!("m_hMyWeapons"),
-        ))
-    }
-
     pub fn get_weapon(&self) -> Option<CWeapon> {
         get_interfaces()
             .entity_list
@@ -296,8 +389,17 @@ impl CEntity {
     }
 
     pub fn get_bone_pos(&self, bone: i32) -> Vec3 {
-        let ptr: usize =
-            self.get_value(netvar::get_offset("DT_BaseAnimating", "m_nForceBone") + 0x1C);
+        static BONE_CACHE: CachedOffset = CachedOffset::new();
+
+        if self.is_null() {
+            return Vec3::empty();
+        }
+
+        let offset = some_or_ret!(
+            BONE_CACHE.get(|| self.data_field_offset("get_bone_pos")),
+            Vec3::empty()
+        );
+        let ptr: usize = self.get_value(offset);
 
         if (ptr as *const usize).is_null() {
             return Vec3::empty();
@@ -4,4 +4,58 @@ pub mod entity;
 pub mod utl_vector;
 pub mod weapon;
 
-pub trait Entity {}
+use crate::interface::Interface;
+use crate::netvar::classes::NetworkClass;
+use crate::netvar::registry::NetvarType;
+use crate::netvar::value::{self, PropValue};
+use crate::utils::error::Error;
+
+/// A wrapper around a networked entity.
+pub trait Entity: Interface {
+    /// Returns `true` if entities of `class` can be wrapped in `Self`.
+    fn matches(class: &NetworkClass) -> bool;
+
+    /// Reads the NetVar `table->name` as `T`, failing if its prop type doesn't fit `T`.
+    /// # Examples
+    /// ```
+    /// use sdk::classes::entity::CEntity;
+    /// use sdk::classes::Entity;
+    /// use sdk::interface::Interface;
+    /// use sdk::netvar::{self, dump::NetvarDump, value::PropValue};
+    ///
+    /// netvar::load_dump(&NetvarDump::parse("# netvar dump v2\nDT_CSPlayer\tm_iHealth\tInt\t0x100\t4\t0\t0\t0\tDT_BasePlayer")?);
+    ///
+    /// let mut memory = [0u8; 0x200];
+    /// let player = unsafe { CEntity::from_raw_unchecked(memory.as_mut_ptr() as *const usize) };
+    ///
+    /// player.set_prop("DT_CSPlayer", "m_iHealth", 100i32)?;
+    ///
+    /// assert_eq!(player.get_prop::<i32>("DT_CSPlayer", "m_iHealth")?, 100);
+    /// assert!(player.get_prop::<f32>("DT_CSPlayer", "m_iHealth").is_err());
+    /// assert!(matches!(player.get_prop_value("DT_CSPlayer", "m_iHealth")?, PropValue::Int(100)));
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    fn get_prop<T: NetvarType + Copy>(&self, table: &str, name: &str) -> Result<T, Error> {
+        unsafe { value::read(self.as_ptr(), table, name) }
+    }
+
+    /// Writes `value` to the NetVar `table->name`, failing if its prop type doesn't fit `T`.
+    fn set_prop<T: NetvarType + Copy>(
+        &self,
+        table: &str,
+        name: &str,
+        value: T,
+    ) -> Result<(), Error> {
+        unsafe { value::write(self.as_ptr(), table, name, value) }
+    }
+
+    /// Reads the NetVar `table->name` as whatever type the prop has.
+    fn get_prop_value(&self, table: &str, name: &str) -> Result<PropValue, Error> {
+        unsafe { value::read_value(self.as_ptr(), table, name) }
+    }
+
+    /// Writes `value` to the NetVar `table->name`, which has to have the same prop type.
+    fn set_prop_value(&self, table: &str, name: &str, value: &PropValue) -> Result<(), Error> {
+        unsafe { value::write_value(self.as_ptr(), table, name, value) }
+    }
+}
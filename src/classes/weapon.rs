@@ -5,6 +5,7 @@ use crate::classes::Entity;
 use crate::definitions::weapon_data;
 use crate::definitions::weapon_data::CWeaponInfo;
 use crate::definitions::weapon_data::{WeaponId, WeaponType};
+use crate::netvar::classes::NetworkClass;
 use crate::utils::math::vector::Vec3;
 use crate::{get_interfaces, get_local_player, utils};
 
@@ -17,97 +18,104 @@ interface!(
     pub update_accuracy_penalty[554]() -> ()
 );
 
-impl Entity for CWeapon {}
+impl Entity for CWeapon {
+    fn matches(class: &NetworkClass) -> bool {
+        class.is_a(obfstr!("DT_BaseCombatWeapon"))
+    }
+}
 
 impl IClientNetworkable<CWeapon> for CWeapon {}
 
 impl CWeapon {
-    netvar!(
-        "DT_BaseCombatWeapon",
-        "m_flNextPrimaryAttack",
-        next_attack,
-        f32
-    );
-    netvar!(
-        "DT_BaseCombatWeapon",
-        "m_flNextPrimaryAttack",
-        0x6D,
-        is_reloading,
-        bool
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_nFallbackPaintKit",
-        get_fallback_paint_kit,
-        set_fallback_paint_kit,
-        u32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_iEntityQuality",
-        get_entity_quality,
-        set_entity_quality,
-        i32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_nFallbackSeed",
-        get_fallback_seed,
-        set_fallback_seed,
-        u32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_nFallbackStatTrak",
-        get_fallback_stat_track,
-        set_fallback_stat_track,
-        i32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_flFallbackWear",
-        get_fallback_wear,
-        set_fallback_wear,
-        f32
-    );
-    netvar!(
-        "DT_BaseCombatWeapon",
-        "m_hWeaponWorldModel",
-        get_weapon_world_model,
-        set_weapon_world_model,
-        i32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_iItemIDHigh",
-        get_id_high,
-        set_id_high,
-        i32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_iItemDefinitionIndex",
-        get_id,
-        set_id,
-        i16
-    );
-    netvar!(
-        "DT_BaseEntity",
-        "m_nModelIndex",
-        get_model_index,
-        set_model_index,
-        u32
-    );
-    netvar!(
-        "DT_BaseAttributableItem",
-        "m_iAccountID",
-        get_account_id,
-        set_account_id,
-        u32
-    );
-    netvar!("DT_CSPlayer", "m_hOwnerEntity", get_owner_entity, i32);
-    netvar!("DT_BaseEntity", "m_vecOrigin", get_origin, Vec3);
-    netvar!("DT_BaseCombatWeapon", "m_iClip1", get_clip, i32);
+    netvars! {
+        CWeapon;
+        (
+            "DT_BaseCombatWeapon",
+            "m_flNextPrimaryAttack",
+            next_attack,
+            f32
+        ),
+        (
+            "DT_BaseCombatWeapon",
+            "m_flNextPrimaryAttack",
+            0x6D,
+            is_reloading,
+            bool
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_nFallbackPaintKit",
+            get_fallback_paint_kit,
+            set_fallback_paint_kit,
+            u32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_iEntityQuality",
+            get_entity_quality,
+            set_entity_quality,
+            i32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_nFallbackSeed",
+            get_fallback_seed,
+            set_fallback_seed,
+            u32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_nFallbackStatTrak",
+            get_fallback_stat_track,
+            set_fallback_stat_track,
+            i32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_flFallbackWear",
+            get_fallback_wear,
+            set_fallback_wear,
+            f32
+        ),
+        (
+            "DT_BaseCombatWeapon",
+            "m_hWeaponWorldModel",
+            get_weapon_world_model,
+            set_weapon_world_model,
+            i32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_iItemIDHigh",
+            get_id_high,
+            set_id_high,
+            i32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_iItemDefinitionIndex",
+            get_id,
+            set_id,
+            i16
+        ),
+        (
+            "DT_BaseEntity",
+            "m_nModelIndex",
+            get_model_index,
+            set_model_index,
+            u32
+        ),
+        (
+            "DT_BaseAttributableItem",
+            "m_iAccountID",
+            get_account_id,
+            set_account_id,
+            u32
+        ),
+        ("DT_CSPlayer", "m_hOwnerEntity", get_owner_entity, i32),
+        ("DT_BaseEntity", "m_vecOrigin", get_origin, Vec3),
+        ("DT_BaseCombatWeapon", "m_iClip1", get_clip, i32),
+    }
 
     pub fn can_fire(&self) -> bool {
         let server_time = get_local_player().get_tickbase() as f32
@@ -176,15 +184,22 @@ impl CWeapon {
 
 create_interface!(CBaseViewModel);
 
-impl Entity for CBaseViewModel {}
+impl Entity for CBaseViewModel {
+    fn matches(class: &NetworkClass) -> bool {
+        class.is_a(obfstr!("DT_BaseViewModel"))
+    }
+}
 
 impl CBaseViewModel {
-    netvar!("DT_BaseViewModel", "m_hWeapon", get_weapon, set_weapon, i32);
-    netvar!(
-        "DT_BaseEntity",
-        "m_nModelIndex",
-        get_model_index,
-        set_model_index,
-        u32
-    );
+    netvars! {
+        CBaseViewModel;
+        ("DT_BaseViewModel", "m_hWeapon", get_weapon, set_weapon, i32),
+        (
+            "DT_BaseEntity",
+            "m_nModelIndex",
+            get_model_index,
+            set_model_index,
+            u32
+        ),
+    }
 }
@@ -0,0 +1,100 @@
+//! The [`Sdk`] context and the guards handing out the globally installed one.
+
+use core::ops::{Deref, DerefMut};
+
+use static_init::lazy::lesser_locked_lazy::{ReadGuard, WriteGuard};
+
+use crate::interface::{Interfaces, LoadOptions, LoadReport};
+use crate::utils::error::Error;
+use crate::version::{self, GameVersion};
+
+/// A loaded set of interfaces.
+///
+/// `sdk::initialize` creates one and installs it globally, but contexts can also be
+/// created and used on their own, for example from fake interfaces in tests.
+/// NetVars are shared between all contexts.
+/// # Examples
+/// ```no_run
+/// use sdk::interface::LoadOptions;
+/// use sdk::Sdk;
+///
+/// let sdk = Sdk::load(&LoadOptions::default())?;
+///
+/// println!("{}", sdk.interfaces().engine.is_ingame());
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+pub struct Sdk {
+    interfaces: Interfaces,
+    report: LoadReport,
+    game_version: Option<GameVersion>,
+}
+
+impl Sdk {
+    /// Resolves all interfaces from the game's modules.
+    pub fn load(options: &LoadOptions) -> Result<Self, Error> {
+        let game_version = options.game_version.clone().or_else(version::detect);
+        let (interfaces, report) = Interfaces::load(options, game_version.as_ref())?;
+
+        Ok(Self {
+            interfaces,
+            report,
+            game_version,
+        })
+    }
+
+    /// Creates a context from already resolved interfaces, its report is empty.
+    pub fn from_interfaces(interfaces: Interfaces) -> Self {
+        Self {
+            interfaces,
+            report: LoadReport::default(),
+            game_version: None,
+        }
+    }
+
+    pub fn interfaces(&self) -> &Interfaces {
+        &self.interfaces
+    }
+
+    pub fn interfaces_mut(&mut self) -> &mut Interfaces {
+        &mut self.interfaces
+    }
+
+    /// Returns what was resolved while loading this context.
+    pub fn report(&self) -> &LoadReport {
+        &self.report
+    }
+
+    /// Returns the game build this context was loaded for, if it is known.
+    pub fn game_version(&self) -> Option<&GameVersion> {
+        self.game_version.as_ref()
+    }
+}
+
+/// Shared access to the interfaces of the installed [`Sdk`].
+pub struct InterfacesGuard(pub(crate) ReadGuard<'static, Option<Sdk>>);
+
+impl Deref for InterfacesGuard {
+    type Target = Interfaces;
+
+    fn deref(&self) -> &Interfaces {
+        // Guards are only handed out while a context is installed.
+        self.0.as_ref().map(Sdk::interfaces).unwrap()
+    }
+}
+
+/// Exclusive access to the interfaces of the installed [`Sdk`].
+pub struct InterfacesGuardMut(pub(crate) WriteGuard<'static, Option<Sdk>>);
+
+impl Deref for InterfacesGuardMut {
+    type Target = Interfaces;
+
+    fn deref(&self) -> &Interfaces {
+        self.0.as_ref().map(Sdk::interfaces).unwrap()
+    }
+}
+
+impl DerefMut for InterfacesGuardMut {
+    fn deref_mut(&mut self) -> &mut Interfaces {
+        self.0.as_mut().map(Sdk::interfaces_mut).unwrap()
+    }
+}
@@ -0,0 +1,40 @@
+use libc::{c_char, c_void};
+
+/// `fieldtype_t` value of fields that embed another datamap.
+pub const FIELD_EMBEDDED: i32 = 10;
+
+/// `typedescription_t`, a single field of a [`DataMap`].
+#[repr(C)]
+pub struct TypeDescription {
+    pub field_type: i32,
+    pub field_name: *const c_char,
+    pub field_offset: i32,
+    pub field_size: u16,
+    pub flags: i16,
+    pub external_name: *const c_char,
+    save_restore_ops: *const c_void,
+    #[cfg(target_os = "windows")]
+    input_func: *const c_void,
+    // Member function pointers are two words in the Itanium ABI.
+    #[cfg(target_os = "linux")]
+    input_func: [*const c_void; 2],
+    pub data_map: *mut DataMap,
+    pub field_size_in_bytes: i32,
+    override_field: *const TypeDescription,
+    override_count: i32,
+    field_tolerance: f32,
+    flat_offset: [i32; 2],
+    flat_group: u16,
+}
+
+/// `datamap_t`, the description of an entity's non-networked (data and prediction) fields.
+#[repr(C)]
+pub struct DataMap {
+    pub data_desc: *mut TypeDescription,
+    pub data_num_fields: i32,
+    pub data_class_name: *const c_char,
+    pub base_map: *mut DataMap,
+    chains_validated: bool,
+    packed_offsets_computed: bool,
+    packed_size: i32,
+}
@@ -2,6 +2,7 @@
 //! or interacts with the game directly.
 
 pub mod bones;
+pub mod datamap;
 pub mod frame_stage;
 pub mod recv_props;
 pub mod weapon_data;
@@ -1,14 +1,18 @@
 use libc::{c_char, c_void};
 
 pub type RecvVarProxyFn =
-    fn(data: *const CRecvProxy, struct_ptr: *mut c_void, out_ptr: *mut c_void);
+    unsafe extern "C" fn(data: *const CRecvProxy, struct_ptr: *mut c_void, out_ptr: *mut c_void);
 type ArrayLengthRecvProxyFn =
-    fn(struct_ptr: *mut c_void, object_id: i32, current_array_length: i32);
-type DataTableRecvVarProxyFn =
-    fn(prop: *const CRecvProp, out_ptr: *mut *mut c_void, data_ptr: *mut c_void, object_id: i32);
+    unsafe extern "C" fn(struct_ptr: *mut c_void, object_id: i32, current_array_length: i32);
+type DataTableRecvVarProxyFn = unsafe extern "C" fn(
+    prop: *const CRecvProp,
+    out_ptr: *mut *mut c_void,
+    data_ptr: *mut c_void,
+    object_id: i32,
+);
 
 #[repr(i32)]
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum EPropType {
     Int = 0,
     Float,
@@ -20,7 +24,38 @@ pub enum EPropType {
     Int64,
 }
 
+impl EPropType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EPropType::Int => "Int",
+            EPropType::Float => "Float",
+            EPropType::Vec => "Vec",
+            EPropType::VecXY => "VecXY",
+            EPropType::String => "String",
+            EPropType::Array => "Array",
+            EPropType::DataTable => "DataTable",
+            EPropType::Int64 => "Int64",
+        }
+    }
+
+    /// Inverse of [`EPropType::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "Int" => EPropType::Int,
+            "Float" => EPropType::Float,
+            "Vec" => EPropType::Vec,
+            "VecXY" => EPropType::VecXY,
+            "String" => EPropType::String,
+            "Array" => EPropType::Array,
+            "DataTable" => EPropType::DataTable,
+            "Int64" => EPropType::Int64,
+            _ => return None,
+        })
+    }
+}
+
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub union CVariantData {
     pub float: f32,
     pub int: i32,
@@ -31,9 +66,10 @@ pub union CVariantData {
 }
 
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct CVariant {
-    data: CVariantData,
-    prop_type: EPropType,
+    pub data: CVariantData,
+    pub prop_type: EPropType,
 }
 
 #[repr(C)]
@@ -51,25 +87,27 @@ pub struct CRecvTable {
 pub struct CRecvProp {
     pub prop_name: *const c_char,
     pub prop_type: EPropType,
-    prop_flags: i32,
-    buffer_size: i32,
+    pub prop_flags: i32,
+    pub buffer_size: i32,
     is_inside_array: i32,
     extra_data_ptr: *const c_void,
-    array_prop: *const CRecvProp,
-    array_length_proxy: ArrayLengthRecvProxyFn,
-    pub proxy_fn: RecvVarProxyFn,
-    data_table_proxy_fn: DataTableRecvVarProxyFn,
+    pub array_prop: *const CRecvProp,
+    array_length_proxy: Option<ArrayLengthRecvProxyFn>,
+    pub proxy_fn: Option<RecvVarProxyFn>,
+    data_table_proxy_fn: Option<DataTableRecvVarProxyFn>,
     pub data_table: *mut CRecvTable,
     pub offset: i32,
-    element_stride: i32,
-    elements_count: i32,
+    pub element_stride: i32,
+    pub elements_count: i32,
     parent_array_prop_name: *const c_char,
 }
 
+/// The data a `RecvVarProxyFn` receives, `value` is what gets written to the entity.
 #[repr(C)]
+#[derive(Copy, Clone)]
 pub struct CRecvProxy {
-    recv_prop: *const CRecvProp,
-    value: CVariant,
-    element_index: i32,
-    object_id: i32,
+    pub recv_prop: *const CRecvProp,
+    pub value: CVariant,
+    pub element_index: i32,
+    pub object_id: i32,
 }
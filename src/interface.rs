@@ -1,36 +1,23 @@
 //! Stores all CS:GO interfaces in one struct ([`Interfaces`])
 
-use alloc::string::ToString;
-use core::mem::MaybeUninit;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
 
 use cstr_core::CStr;
-use fehler::throws;
 use libc::{c_char, c_void};
 
+use crate::classes::entity::CEntity;
 use crate::interfaces::*;
+use crate::netvar::dump::NetvarDump;
+use crate::netvar::registry;
 use crate::utils::error::Error;
 use crate::utils::error::Error::NotFound;
-use crate::utils::memory::{get_module_handle, get_proc_address, pattern_scan};
+use crate::utils::memory::{get_module_handle, get_proc_address};
 use crate::utils::platform::modules;
-use crate::utils::{memory, patterns};
-
-const CLIENT: u32 = hash!("VClient018");
-const ENTITY_LIST: u32 = hash!("VClientEntityList003");
-const ENGINE: u32 = hash!("VEngineClient014");
-const VGUI_PANEL: u32 = hash!("VGUI_Panel009");
-const VGUI_SURFACE: u32 = hash!("VGUI_Surface031");
-const INPUT_SYSTEM: u32 = hash!("InputSystemVersion001");
-const RENDER_VIEW: u32 = hash!("VEngineRenderView014");
-const CVAR: u32 = hash!("VEngineCvar007");
-const ENGINE_TRACE: u32 = hash!("EngineTraceClient004");
-const ENGINE_SOUND: u32 = hash!("IEngineSoundClient003");
-const MAT_SYSTEM: u32 = hash!("VMaterialSystem080");
-const MODEL_RENDER: u32 = hash!("VEngineModel016");
-const MODEL_INFO: u32 = hash!("VModelInfoClient004");
-const LOCALIZE: u32 = hash!("Localize_001");
-const PHYS_SURFACE_PROPS: u32 = hash!("VPhysicsSurfaceProps001");
-const PREDICTION: u32 = hash!("VClientPrediction001");
-const GAME_EVENT_MGR: u32 = hash!("GAMEEVENTSMANAGER002");
+use crate::utils::signature_cache::SignatureCache;
+use crate::utils::signature_db::{Match, SignatureDb};
+use crate::version::GameVersion;
 
 /// Every interface implements this trait.
 pub trait Interface {
@@ -44,6 +31,9 @@ pub trait Interface {
 }
 
 /// Struct that stores every interface.
+///
+/// Interfaces that are only reachable through a pattern or an export are `Option`s,
+/// see [`LoadOptions`] to make them required.
 pub struct Interfaces {
     pub client: client::IClient,
     pub client_mode: *mut usize,
@@ -51,9 +41,9 @@ pub struct Interfaces {
     pub vgui_panel: panel::IPanel,
     pub entity_list: entity_list::IEntityList,
     pub engine: engine::IEngine,
-    pub glow_object_manager: &'static mut glow::IGlowObjectManager,
+    pub glow_object_manager: Option<&'static mut glow::IGlowObjectManager>,
     pub input_system: input_system::IInputSystem,
-    pub input: input::IInput,
+    pub input: Option<input::IInput>,
     pub global_vars: &'static globals::IGlobalVars,
     pub render_view: render_view::IRenderView,
     pub cvar: convar::ICVar,
@@ -65,102 +55,539 @@ pub struct Interfaces {
     pub localize: localize::ILocalize,
     pub physics_surface_props: physics_surface_props::IPhysicsSurfaceProps,
     pub prediction: prediction::IPrediction,
-    pub view_render_beams: view_render_beams::IViewRenderBeams,
+    pub view_render_beams: Option<view_render_beams::IViewRenderBeams>,
     pub game_event: game_events::IGameEventManager,
-    pub key_values_system: key_values_system::IKeyValuesSystem,
+    pub key_values_system: Option<key_values_system::IKeyValuesSystem>,
+    /// `C_CSPlayer::IsOtherEnemy`, not an interface but resolved along with them.
+    pub is_other_enemy: Option<usize>,
 }
 
-impl Default for Interfaces {
-    #[allow(invalid_value)]
-    fn default() -> Self {
-        unsafe { MaybeUninit::uninit().assume_init() }
+unsafe impl Send for Interfaces {}
+
+impl Interfaces {
+    /// Resolves every interface, pattern and export.
+    ///
+    /// Fails with [`Error::Load`] if anything required is missing, the report
+    /// is returned either way.
+    pub(crate) fn load(
+        options: &LoadOptions,
+        game_version: Option<&GameVersion>,
+    ) -> Result<(Self, LoadReport), Error> {
+        let mut report = LoadReport::default();
+
+        match unsafe { Self::resolve(options, game_version, &mut report) } {
+            Some(interfaces) if report.is_complete() => Ok((interfaces, report)),
+            _ => Err(Error::Load { report }),
+        }
+    }
+
+    unsafe fn resolve(
+        options: &LoadOptions,
+        game_version: Option<&GameVersion>,
+        report: &mut LoadReport,
+    ) -> Option<Self> {
+        let client = report.interface::<client::IClient>(modules::CLIENT, obfstr!("VClient"));
+        let engine = report.interface(modules::ENGINE, obfstr!("VEngineClient"));
+        let vgui_panel = report.interface(modules::VGUI2, obfstr!("VGUI_Panel"));
+        let entity_list = report.interface(modules::CLIENT, obfstr!("VClientEntityList"));
+        let vgui_surface = report.interface(modules::VGUI, obfstr!("VGUI_Surface"));
+        let input_system = report.interface(modules::INPUT_SYSTEM, obfstr!("InputSystemVersion"));
+        let render_view = report.interface(modules::ENGINE, obfstr!("VEngineRenderView"));
+        let cvar = report.interface(modules::VSTD_LIB, obfstr!("VEngineCvar"));
+        let engine_trace = report.interface(modules::ENGINE, obfstr!("EngineTraceClient"));
+        let engine_sound = report.interface(modules::ENGINE, obfstr!("IEngineSoundClient"));
+        let material_system =
+            report.interface(modules::MATERIAL_SYSTEM, obfstr!("VMaterialSystem"));
+        let model_render = report.interface(modules::ENGINE, obfstr!("VEngineModel"));
+        let model_info = report.interface(modules::ENGINE, obfstr!("VModelInfoClient"));
+        let localize = report.interface(modules::LOCALIZE, obfstr!("Localize_"));
+        let physics_surface_props =
+            report.interface(modules::PHYSICS, obfstr!("VPhysicsSurfaceProps"));
+        let prediction = report.interface(modules::CLIENT, obfstr!("VClientPrediction"));
+        let game_event = report.interface(modules::ENGINE, obfstr!("GAMEEVENTSMANAGER"));
+
+        let bundled_signatures;
+        let signature_db = match &options.signature_db {
+            Some(signature_db) => signature_db,
+            None => {
+                bundled_signatures = SignatureDb::bundled();
+                &bundled_signatures
+            }
+        };
+        let build = game_version.map(GameVersion::build);
+        let patterns = match &options.signature_cache {
+            Some(cache) => {
+                let mut cache = cache.clone();
+                let patterns = signature_db.resolve_all_cached(build, &mut cache);
+
+                report.signature_cache = Some(cache);
+                patterns
+            }
+            None => signature_db.resolve_all(build),
+        };
+
+        let glow_object_manager = report
+            .pattern(
+                obfstr!("IGlowMgr"),
+                options.require_glow_object_manager,
+                patterns.get(obfstr!("IGlowMgr")),
+            )
+            // The manager starts at the operand of the match, its first field is the pointer
+            // to the glow object vector.
+            .map(|address| &mut *(address as *mut glow::IGlowObjectManager));
+        let input = report
+            .pattern(
+                obfstr!("IInput"),
+                options.require_input,
+                patterns.get(obfstr!("IInput")),
+            )
+            .map(|address| input::IInput::from_raw_unchecked(address as *mut usize));
+        let view_render_beams = report
+            .pattern(
+                obfstr!("IViewRenderBeams"),
+                options.require_view_render_beams,
+                patterns.get(obfstr!("IViewRenderBeams")),
+            )
+            .map(|address| {
+                view_render_beams::IViewRenderBeams::from_raw_unchecked(address as *mut usize)
+            });
+        let key_values_system = report
+            .address(
+                LoadKind::Export,
+                obfstr!("KeyValuesSystem"),
+                options.require_key_values_system,
+                get_proc_address(
+                    get_module_handle(modules::VSTD_LIB),
+                    cstr!("KeyValuesSystem"),
+                )
+                .map(|address| address as usize),
+            )
+            .map(|address| key_values_system::IKeyValuesSystem::from_raw_unchecked(address as _));
+        let is_other_enemy = report.pattern(
+            obfstr!("IsOtherEnemy"),
+            false,
+            patterns.get(obfstr!("IsOtherEnemy")),
+        );
+
+        let client = client?;
+
+        Some(Self {
+            client_mode: **(((*((*(client.as_ptr() as *mut *mut usize)).offset(10))) + 5)
+                as *mut *mut _),
+            global_vars: &*(**(((*((*(client.as_ptr() as *mut *mut usize)).offset(11))) + 10)
+                as *mut *mut usize) as *const globals::IGlobalVars),
+            client,
+            engine: engine?,
+            glow_object_manager,
+            vgui_panel: vgui_panel?,
+            entity_list: entity_list?,
+            vgui_surface: vgui_surface?,
+            input_system: input_system?,
+            input,
+            render_view: render_view?,
+            cvar: cvar?,
+            engine_trace: engine_trace?,
+            engine_sound: engine_sound?,
+            material_system: material_system?,
+            model_render: model_render?,
+            model_info: model_info?,
+            localize: localize?,
+            physics_surface_props: physics_surface_props?,
+            prediction: prediction?,
+            view_render_beams,
+            game_event: game_event?,
+            key_values_system,
+            is_other_enemy,
+        })
     }
 }
 
-unsafe impl Send for Interfaces {}
+/// Decides which of the optional [`Interfaces`] have to be found for
+/// `sdk::initialize_with` to succeed, and whether every `netvar!` has to resolve.
+///
+/// Every other interface is always required.
+/// # Examples
+/// ```no_run
+/// use sdk::interface::LoadOptions;
+///
+/// let report = sdk::initialize_with(&LoadOptions {
+///     require_glow_object_manager: true,
+///     ..LoadOptions::default()
+/// })?;
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct LoadOptions {
+    pub require_glow_object_manager: bool,
+    pub require_input: bool,
+    pub require_view_render_beams: bool,
+    pub require_key_values_system: bool,
+    /// Fail if a NetVar declared through `netvar!` is missing or has the wrong type.
+    pub require_netvars: bool,
+    /// NetVars to fall back to if they can't be scanned, e.g. a dump saved by an earlier run.
+    pub netvar_cache: Option<NetvarDump>,
+    /// Signatures to use instead of [`SignatureDb::bundled`].
+    pub signature_db: Option<SignatureDb>,
+    /// Matches from an earlier run, used instead of scanning if they are still valid.
+    pub signature_cache: Option<SignatureCache>,
+    /// Picks the signature variants that apply to it, detected from `steam.inf` if `None`.
+    /// Every variant is tried if it can't be detected either.
+    pub game_version: Option<GameVersion>,
+}
 
-impl Interfaces {
-    #[throws(Error)]
-    pub(crate) fn load() -> Self {
-        unsafe {
-            let client_interface = get_interface::<client::IClient>(modules::CLIENT, CLIENT)?;
-
-            Self {
-                client_mode: **(((*((*(client_interface.as_ptr() as *mut *mut usize)).offset(10)))
-                    + 5) as *mut *mut _),
-                global_vars: &*(**(((*((*(client_interface.as_ptr() as *mut *mut usize))
-                    .offset(11)))
-                    + 10) as *mut *mut usize)
-                    as *const globals::IGlobalVars),
-                client: client_interface,
-                engine: get_interface(modules::ENGINE, ENGINE)?,
-                glow_object_manager: core::mem::transmute::<_, &mut glow::IGlowObjectManager>(
-                    memory::read_mut::<usize>(
-                        some_or_ret!(
-                            pattern_scan(modules::CLIENT, patterns::GLOW_MANAGER),
-                            Err(NotFound {
-                                item: obfstr!("IGlowMgr").into()
-                            })
-                        ) as usize
-                            + 0x3,
-                    ),
-                ),
-                vgui_panel: get_interface(modules::VGUI2, VGUI_PANEL)?,
-                entity_list: get_interface(modules::CLIENT, ENTITY_LIST)?,
-                vgui_surface: get_interface(modules::VGUI, VGUI_SURFACE)?,
-                input_system: get_interface(modules::INPUT_SYSTEM, INPUT_SYSTEM)?,
-                input: input::IInput::from_raw_unchecked(memory::read::<*mut usize>(
-                    some_or_ret!(
-                        pattern_scan(modules::CLIENT, patterns::INPUT_INTERFACE),
-                        Err(NotFound {
-                            item: obfstr!("IInput").into()
-                        })
-                    ) as usize
-                        + 0x1,
-                )),
-                render_view: get_interface(modules::ENGINE, RENDER_VIEW)?,
-                cvar: get_interface(modules::VSTD_LIB, CVAR)?,
-                engine_trace: get_interface(modules::ENGINE, ENGINE_TRACE)?,
-                engine_sound: get_interface(modules::ENGINE, ENGINE_SOUND)?,
-                material_system: get_interface(modules::MATERIAL_SYSTEM, MAT_SYSTEM)?,
-                model_render: get_interface(modules::ENGINE, MODEL_RENDER)?,
-                model_info: get_interface(modules::ENGINE, MODEL_INFO)?,
-                localize: get_interface(modules::LOCALIZE, LOCALIZE)?,
-                physics_surface_props: get_interface(modules::PHYSICS, PHYS_SURFACE_PROPS)?,
-                prediction: get_interface(modules::CLIENT, PREDICTION)?,
-                view_render_beams: view_render_beams::IViewRenderBeams::from_raw_unchecked(
-                    *((some_or_ret!(
-                        pattern_scan(modules::CLIENT, patterns::VIEW_RENDER_BEAMS),
-                        Err(NotFound {
-                            item: obfstr!("IViewRenderBeams").into()
-                        })
-                    ) as usize
-                        + 0x1) as *mut usize) as *mut usize,
-                ),
-                game_event: get_interface(modules::ENGINE, GAME_EVENT_MGR)?,
-                key_values_system: key_values_system::IKeyValuesSystem::from_raw_unchecked(
-                    some_or_ret!(
-                        get_proc_address(
-                            get_module_handle(modules::VSTD_LIB),
-                            cstr!("KeyValuesSystem")
-                        ),
-                        Err(NotFound {
-                            item: obfstr!("KeyValuesSystem").into()
-                        })
-                    ) as *const usize,
-                ),
+impl LoadOptions {
+    /// Requires everything, which is how the SDK used to behave.
+    pub fn strict() -> Self {
+        Self {
+            require_glow_object_manager: true,
+            require_input: true,
+            require_view_render_beams: true,
+            require_key_values_system: true,
+            require_netvars: true,
+            netvar_cache: None,
+            signature_db: None,
+            signature_cache: None,
+            game_version: None,
+        }
+    }
+}
+
+/// How a [`LoadEntry`] was looked up.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LoadKind {
+    Interface,
+    Pattern,
+    Export,
+    /// A NetVar declared through `netvar!`, checked after the NetVars were scanned.
+    Netvar,
+    /// A non-networked field, checked against the datamaps of the local player.
+    DataField,
+}
+
+/// A successfully resolved [`LoadEntry`].
+#[derive(Clone, Debug)]
+pub struct Resolved {
+    /// Full name of what was found, e.g. `"VClient018"` for the `"VClient"` interface
+    /// or `"IInput (variant 2)"` for a pattern.
+    pub name: String,
+    /// The address that was found, for NetVars their offset.
+    pub address: usize,
+}
+
+/// Outcome of a single interface, pattern or export lookup.
+#[derive(Clone, Debug)]
+pub struct LoadEntry {
+    pub kind: LoadKind,
+    /// The name that was looked up, interfaces are listed without their version.
+    pub item: String,
+    pub required: bool,
+    pub result: Result<Resolved, Error>,
+}
+
+/// Everything `sdk::initialize` tried to resolve.
+/// # Examples
+/// ```no_run
+/// let report = sdk::initialize()?;
+///
+/// for entry in report.failed() {
+///     println!("{:?} {} is unavailable", entry.kind, entry.item);
+/// }
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LoadReport {
+    pub entries: Vec<LoadEntry>,
+    /// The `signature_cache` of the `LoadOptions` with the matches that had to be scanned
+    /// for, save it for the next run.
+    pub signature_cache: Option<SignatureCache>,
+}
+
+impl LoadReport {
+    /// Returns the entry for `item`, if it was looked up.
+    pub fn get(&self, item: &str) -> Option<&LoadEntry> {
+        self.entries.iter().find(|entry| entry.item == item)
+    }
+
+    /// Returns every entry that could not be resolved.
+    pub fn failed(&self) -> impl Iterator<Item = &LoadEntry> {
+        self.entries.iter().filter(|entry| entry.result.is_err())
+    }
+
+    /// Returns `true` if everything required was resolved.
+    pub fn is_complete(&self) -> bool {
+        !self.failed().any(|entry| entry.required)
+    }
+
+    /// Adds an entry for every NetVar declared through `netvar!`, looked up in `netvars`.
+    pub(crate) fn check_netvars(&mut self, netvars: &NetvarDump, required: bool) {
+        for (declaration, result) in registry::validate_dump(netvars) {
+            self.entries.push(LoadEntry {
+                kind: LoadKind::Netvar,
+                item: declaration.to_string(),
+                required,
+                result: result.map(|netvar| Resolved {
+                    name: format!("{}->{}", declaration.table, declaration.name),
+                    address: netvar.offset,
+                }),
+            });
+        }
+    }
+
+    /// Adds an entry for every field read through the datamaps, looked up in those of
+    /// `entity` and at its NetVar in `netvars`.
+    ///
+    /// The entries are optional, an accessor whose field is missing reads it next to its NetVar.
+    pub(crate) fn check_data_fields(&mut self, entity: Option<&CEntity>, netvars: &NetvarDump) {
+        for declaration in CEntity::data_fields() {
+            self.entries.push(LoadEntry {
+                kind: LoadKind::DataField,
+                item: format!("CEntity::{}", declaration.accessor),
+                required: false,
+                result: declaration
+                    .check(entity, netvars)
+                    .map(|(name, address)| Resolved { name, address }),
+            });
+        }
+    }
+
+    unsafe fn interface<T: Interface>(&mut self, module: *const i8, prefix: &str) -> Option<T> {
+        let result = get_interface_versioned::<T>(module, prefix);
+
+        self.entries.push(LoadEntry {
+            kind: LoadKind::Interface,
+            item: prefix.to_string(),
+            required: true,
+            result: result.as_ref().map_or_else(
+                |error| Err(error.clone()),
+                |(interface, name)| {
+                    Ok(Resolved {
+                        name: name.clone(),
+                        address: interface.as_ptr() as usize,
+                    })
+                },
+            ),
+        });
+
+        result.ok().map(|(interface, _)| interface)
+    }
+
+    fn address(
+        &mut self,
+        kind: LoadKind,
+        item: &str,
+        required: bool,
+        address: Option<usize>,
+    ) -> Option<usize> {
+        self.entries.push(LoadEntry {
+            kind,
+            item: item.to_string(),
+            required,
+            result: address
+                .map(|address| Resolved {
+                    name: item.to_string(),
+                    address,
+                })
+                .ok_or_else(|| NotFound {
+                    item: item.to_string(),
+                }),
+        });
+
+        address
+    }
+
+    /// Records which variant of the signature db entry `item` was found.
+    fn pattern(
+        &mut self,
+        item: &str,
+        required: bool,
+        result: Result<Match, Error>,
+    ) -> Option<usize> {
+        self.entries.push(LoadEntry {
+            kind: LoadKind::Pattern,
+            item: item.to_string(),
+            required,
+            result: result.clone().map(|found| Resolved {
+                name: match found.cached {
+                    true => format!("{} (variant {}, cached)", item, found.variant + 1),
+                    false => format!("{} (variant {})", item, found.variant + 1),
+                },
+                address: found.address,
+            }),
+        });
+
+        result.ok().map(|found| found.address)
+    }
+}
+
+impl Display for LoadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut failed = self.failed().filter(|entry| entry.required).peekable();
+
+        if failed.peek().is_none() {
+            return write!(f, "nothing");
+        }
+
+        for (i, entry) in failed.enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
             }
+
+            write!(f, "{} ({:?})", entry.item, entry.kind)?;
         }
+
+        Ok(())
     }
 }
 
-#[cfg(target_os = "windows")]
-pub(crate) unsafe fn get_interface<T: Interface>(
+/// Walks the `CreateInterface` registry of `module` and instantiates the interface
+/// whose hashed name equals `interface`.
+///
+/// Use [`get_interface_versioned`] if the interface version is allowed to change.
+/// # Safety
+/// `module` has to be a null terminated module name.
+pub unsafe fn get_interface<T: Interface>(module: *const i8, interface: u32) -> Result<T, Error> {
+    let registered = some_or_ret!(
+        interface_registry(module)?.find(|registered| obfstr::hash(registered.name()) == interface),
+        Err(NotFound {
+            item: interface.to_string(),
+        })
+    );
+
+    Ok(registered.create())
+}
+
+/// Looks up an interface by its name without the version suffix, e.g. `"VClient"`
+/// instead of `"VClient018"`, and instantiates the highest version the module exports.
+///
+/// Only names consisting of `prefix` followed by nothing but digits are considered,
+/// so `"VClient"` will never resolve to `"VClientEntityList003"`.
+/// Returns the interface together with its full name.
+/// # Examples
+/// ```no_run
+/// use sdk::interface::get_interface_versioned;
+/// use sdk::interfaces::client::IClient;
+/// use sdk::utils::platform::modules;
+///
+/// let (client, name) = unsafe { get_interface_versioned::<IClient>(modules::CLIENT, "VClient")? };
+///
+/// println!("using {}", name);
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+/// # Safety
+/// `module` has to be a null terminated module name.
+pub unsafe fn get_interface_versioned<T: Interface>(
     module: *const i8,
-    interface: u32,
-) -> Result<T, Error> {
+    prefix: &str,
+) -> Result<(T, String), Error> {
+    let (_, registered) = some_or_ret!(
+        interface_registry(module)?
+            .filter_map(|registered| {
+                parse_version(registered.name(), prefix).map(|version| (version, registered))
+            })
+            .max_by_key(|(version, _)| *version),
+        Err(NotFound {
+            item: prefix.to_string(),
+        })
+    );
+
+    Ok((registered.create(), registered.name().to_string()))
+}
+
+/// Returns an iterator over every interface `module` registered through `CreateInterface`.
+///
+/// On windows the registry is located by disassembling the exported `CreateInterface`
+/// function, on linux through the `s_pInterfaceRegs` symbol.
+/// # Examples
+/// ```no_run
+/// use sdk::interface::interface_registry;
+/// use sdk::utils::platform::modules;
+///
+/// for interface in unsafe { interface_registry(modules::ENGINE)? } {
+///     println!("{} {:p}", interface.name(), interface.factory());
+/// }
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+/// # Safety
+/// `module` has to be a null terminated module name. The returned iterator
+/// must not outlive the module.
+pub unsafe fn interface_registry(module: *const i8) -> Result<InterfaceRegistry, Error> {
+    Ok(InterfaceRegistry {
+        current: interface_list(module)?,
+    })
+}
+
+/// Iterator over a module's `CreateInterface` registry, see [`interface_registry`].
+pub struct InterfaceRegistry {
+    current: *mut InterfaceLinkedList,
+}
+
+impl Iterator for InterfaceRegistry {
+    type Item = RegisteredInterface;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.is_null() {
+            return None;
+        }
+
+        let registered = RegisteredInterface { node: self.current };
+        self.current = unsafe { (*self.current).next };
+
+        Some(registered)
+    }
+}
+
+/// A single entry of a module's interface registry.
+#[derive(Copy, Clone)]
+pub struct RegisteredInterface {
+    node: *mut InterfaceLinkedList,
+}
+
+impl RegisteredInterface {
+    /// Returns the full interface name, including its version, e.g. `"VClient018"`.
+    ///
+    /// The name is stored in the module, so it is only borrowed as long as the entry.
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr((*self.node).name) }
+            .to_str()
+            .unwrap_or("?")
+    }
+
+    /// Returns the address of the factory function that creates the interface.
+    pub fn factory(&self) -> *const c_void {
+        unsafe { (*self.node).func as *const c_void }
+    }
+
+    /// Calls the factory function and returns the interface instance.
+    /// # Safety
+    /// The factory is game code, calling it is only safe as long as the module is loaded.
+    pub unsafe fn instance(&self) -> *const c_void {
+        ((*self.node).func)()
+    }
+
+    /// Calls the factory function and wraps the instance in `T`.
+    /// # Safety
+    /// See [`RegisteredInterface::instance`], `T` has to match the interface.
+    pub unsafe fn create<T: Interface>(&self) -> T {
+        T::from_raw_unchecked(self.instance() as _)
+    }
+}
+
+/// Returns the version of `name` if it is `prefix` followed by a numeric suffix.
+fn parse_version(name: &str, prefix: &str) -> Option<u32> {
+    let suffix = name.strip_prefix(prefix)?;
+
+    if suffix.is_empty() || !suffix.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    suffix.parse().ok()
+}
+
+/// Returns the head of the module's `CreateInterface` linked list.
+#[cfg(target_os = "windows")]
+unsafe fn interface_list(module: *const i8) -> Result<*mut InterfaceLinkedList, Error> {
+    use crate::utils::memory::{self, get_module_handle, get_proc_address};
     use core::mem::size_of;
-    use memory::{get_module_handle, get_proc_address};
 
     let fn_addr = some_or_ret!(
         get_proc_address(get_module_handle(module), cstr!("CreateInterface")),
@@ -172,60 +599,35 @@ pub(crate) unsafe fn get_interface<T: Interface>(
     let addr = fn_addr as usize + 5;
     let disp = memory::read::<usize>(addr);
 
-    let mut current_interface = transmute!(
+    Ok(transmute!(
         **((addr + size_of::<u32>() + disp + 6) as *mut *mut usize),
         *mut InterfaceLinkedList
-    );
-
-    while !current_interface.is_null() {
-        let interface_name = CStr::from_ptr((*current_interface).name)
-            .to_str()
-            .unwrap_or("?");
-
-        if obfstr::hash(interface_name).eq(&interface) {
-            let fn_addr = (*current_interface).func;
-            return Ok(T::from_raw_unchecked(fn_addr() as _));
-        }
-
-        current_interface = (*current_interface).next;
-    }
-
-    Err(NotFound {
-        item: interface.to_string(),
-    })
+    ))
 }
 
+/// Returns the head of the module's `s_pInterfaceRegs` linked list.
 #[cfg(target_os = "linux")]
-pub(crate) unsafe fn get_interface<T: Interface>(
-    module: *const i8,
-    interface: u32,
-) -> Result<T, Error> {
+unsafe fn interface_list(module: *const i8) -> Result<*mut InterfaceLinkedList, Error> {
     use libc::{RTLD_LOCAL, RTLD_NOLOAD, RTLD_NOW};
     let module = libc::dlopen(module, RTLD_NOLOAD | RTLD_NOW | RTLD_LOCAL);
 
-    let mut current_interface = transmute!(
-        libc::dlsym(module, cstr!("s_pInterfaceRegs")),
-        *mut InterfaceLinkedList
-    );
-
-    libc::dlclose(module);
+    if module.is_null() {
+        return Err(NotFound {
+            item: obfstr!("interface module").into(),
+        });
+    }
 
-    while !current_interface.is_null() {
-        let interface_name = CStr::from_ptr((*current_interface).name)
-            .to_str()
-            .unwrap_or("?");
+    let interface_regs = libc::dlsym(module, cstr!("s_pInterfaceRegs"));
 
-        if obfstr::hash(interface_name).eq(&interface) {
-            let fn_addr = (*current_interface).func;
-            return Ok(T::from_raw_unchecked(fn_addr() as _));
-        }
+    libc::dlclose(module);
 
-        current_interface = (*current_interface).next;
+    if interface_regs.is_null() {
+        return Err(NotFound {
+            item: obfstr!("s_pInterfaceRegs").into(),
+        });
     }
 
-    Err(NotFound {
-        item: interface.to_string(),
-    })
+    Ok(*(interface_regs as *mut *mut InterfaceLinkedList))
 }
 
 #[repr(C)]
@@ -1,6 +1,5 @@
 use crate::classes::entity::CEntity;
 use crate::classes::Entity;
-use crate::interface::Interface;
 use crate::utils::memory::NotNull;
 
 interface!(
@@ -11,15 +10,18 @@ interface!(
 );
 
 impl IEntityList {
-    pub fn get_entity_by_id<T: Entity + Interface>(&self, id: i32) -> Option<T> {
+    /// Returns the entity with the index `id`, or `None` if there is none or its
+    /// class doesn't match `T`.
+    pub fn get_entity_by_id<T: Entity>(&self, id: i32) -> Option<T> {
         self.get_entity_by_id_virtual(id)
             .get()
-            .map(|entity| unsafe { T::from_raw_unchecked(entity.as_ptr()) })
+            .and_then(|entity| entity.downcast())
     }
 
-    pub fn get_entity_from_handle<T: Entity + Interface>(&self, handle: i32) -> Option<T> {
+    /// Same as [`IEntityList::get_entity_by_id`], but looks the entity up by its handle.
+    pub fn get_entity_from_handle<T: Entity>(&self, handle: i32) -> Option<T> {
         self.get_entity_from_handle_virtual(handle)
             .get()
-            .map(|entity| unsafe { T::from_raw_unchecked(entity.as_ptr()) })
+            .and_then(|entity| entity.downcast())
     }
 }
@@ -1,4 +1,4 @@
-use winapi::ctypes::c_void;
+use libc::c_void;
 
 interface!(
     IKeyValuesSystem,
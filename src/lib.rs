@@ -34,50 +34,144 @@
 extern crate alloc;
 #[macro_use]
 extern crate obfstr;
+#[cfg(feature = "std")]
+extern crate std;
 
 use alloc::vec::Vec;
 
-use fehler::throws;
+use fehler::{throw, throws};
 use static_init::dynamic;
-use static_init::lazy::lesser_locked_lazy::{ReadGuard, WriteGuard};
 
 use crate::classes::entity::CEntity;
-use crate::interface::Interfaces;
+pub use crate::context::{InterfacesGuard, InterfacesGuardMut, Sdk};
+use crate::interface::{LoadOptions, LoadReport};
 use crate::utils::error::Error;
 use crate::utils::math::get_player_distance;
+use crate::utils::signature_db::{self, SignatureDb};
+use crate::version::GameVersion;
 
 #[macro_use]
 pub mod utils;
 #[allow(dead_code)]
 pub mod classes;
+mod context;
 pub mod definitions;
 pub mod interface;
 #[allow(dead_code)]
 pub mod interfaces;
 pub mod netvar;
+pub mod version;
 
 /* TODO
   document all public functions and types, atleast the ones that could need some.
 */
 
 #[dynamic]
-static mut INTERFACES: Interfaces = Interfaces::default();
+static mut SDK: Option<Sdk> = None;
 
-/// Initializes the static `Interfaces` struct and loads all NetVars.
+/// Initializes the global [`Sdk`] context and loads all NetVars.
+///
+/// Optional interfaces that could not be found are `None`, the returned
+/// [`LoadReport`] tells which ones and why.
 #[throws(Error)]
-pub fn initialize() {
-    *INTERFACES.write() = Interfaces::load()?;
-    netvar::manager::scan()?;
+pub fn initialize() -> LoadReport {
+    initialize_with(&LoadOptions::default())?
+}
+
+/// Same as [`initialize`], but lets you decide which optional interfaces are required.
+///
+/// The report also lists every NetVar declared through `netvar!` that is missing or
+/// has an unexpected type, set `require_netvars` to fail in that case. If the NetVars
+/// can't be scanned, the ones from `netvar_cache` are used instead. Fields that aren't
+/// networked are checked against the datamaps of the local player, if there is one.
+///
+/// Nothing is installed unless the report is complete, a failed call leaves the
+/// NetVars, signatures and context of an earlier one in place.
+#[throws(Error)]
+pub fn initialize_with(options: &LoadOptions) -> LoadReport {
+    let sdk = Sdk::load(options)?;
+
+    let scan = netvar::manager::scan(&sdk.interfaces().client);
+    let netvars = match &scan {
+        Ok(scan) => &scan.netvars,
+        Err(error) => options.netvar_cache.as_ref().ok_or_else(|| error.clone())?,
+    };
+
+    let mut report = sdk.report().clone();
+    report.check_netvars(netvars, options.require_netvars);
+
+    let interfaces = sdk.interfaces();
+    let local_player = interfaces
+        .entity_list
+        .get_entity_by_id::<CEntity>(interfaces.engine.get_local_player());
+    report.check_data_fields(local_player.as_ref(), netvars);
+
+    if !report.is_complete() {
+        throw!(Error::Load { report });
+    }
+
+    match scan {
+        Ok(scan) => scan.install(),
+        Err(_) => netvar::load_dump(netvars),
+    }
+
+    signature_db::install(
+        options
+            .signature_db
+            .clone()
+            .unwrap_or_else(SignatureDb::bundled),
+        sdk.game_version().map(GameVersion::build),
+        sdk.report().signature_cache.clone(),
+    );
+
+    install(sdk);
+
+    report
+}
+
+/// Installs `sdk` as the global context and returns the previous one, if any.
+///
+/// This is what [`initialize`] does after loading, use it to run the SDK's
+/// helpers against a context you created yourself.
+pub fn install(sdk: Sdk) -> Option<Sdk> {
+    SDK.write().replace(sdk)
+}
+
+/// Returns the interfaces of the global context, or `Error::Uninitialized` if
+/// [`initialize`] has not succeeded yet.
+pub fn try_get_interfaces() -> Result<InterfacesGuard, Error> {
+    let guard = SDK.read();
+
+    if guard.is_none() {
+        return Err(Error::Uninitialized);
+    }
+
+    Ok(InterfacesGuard(guard))
+}
+
+/// Mutable version of [`try_get_interfaces`].
+pub fn try_get_interfaces_mut() -> Result<InterfacesGuardMut, Error> {
+    let guard = SDK.write();
+
+    if guard.is_none() {
+        return Err(Error::Uninitialized);
+    }
+
+    Ok(InterfacesGuardMut(guard))
 }
 
 /// Returns a static reference to the `Interfaces` struct.
-pub fn get_interfaces() -> ReadGuard<'static, Interfaces> {
-    INTERFACES.read()
+/// # Panics
+/// This will panic if the SDK is not initialized, use [`try_get_interfaces`] to handle that case.
+pub fn get_interfaces() -> InterfacesGuard {
+    try_get_interfaces().expect("sdk is not initialized")
 }
 
 /// Returns a static mutable reference to the `Interfaces` struct.
-pub fn get_interfaces_mut() -> WriteGuard<'static, Interfaces> {
-    INTERFACES.write()
+/// # Panics
+/// This will panic if the SDK is not initialized, use [`try_get_interfaces_mut`] to handle that case.
+pub fn get_interfaces_mut() -> InterfacesGuardMut {
+    try_get_interfaces_mut().expect("sdk is not initialized")
 }
 
 /// Returns the closest entity from the localplayers position, if any.
@@ -1,99 +1,760 @@
 //! NetVar Manager.
 
+pub mod classes;
+pub mod datamap;
+pub mod diff;
+pub mod dump;
+pub mod registry;
+pub mod value;
+
 use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 use static_init::dynamic;
 
+use crate::definitions::recv_props::EPropType;
+use crate::utils::error::Error;
 use alloc::string::{String, ToString};
 
 #[dynamic]
-static mut NETVARS: BTreeMap<String, usize> = BTreeMap::new();
+static mut NETVARS: BTreeMap<String, Netvar> = BTreeMap::new();
 #[dynamic]
 static mut PROPS: BTreeMap<String, usize> = BTreeMap::new();
+/// Changes whenever `NETVARS` is filled, invalidating every [`CachedOffset`].
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// A networked property and everything its `RecvProp` tells about it.
+#[derive(Clone, Debug)]
+pub struct Netvar {
+    /// The `RecvTable` the property is declared in, e.g. `DT_BaseEntity` or `DT_LocalPlayerExclusive`.
+    pub table: String,
+    pub name: String,
+    pub prop_type: EPropType,
+    pub flags: i32,
+    /// Offset from the start of the entity, including the offsets of all parent tables.
+    pub offset: usize,
+    /// How many bytes the value takes up in the entity, `0` if unknown.
+    ///
+    /// `Int` props are 1, 2 or 4 bytes, depending on the proxy that writes them. Props
+    /// with a custom proxy have an unknown size.
+    pub size: usize,
+    pub elements_count: i32,
+    pub element_stride: i32,
+}
+
+impl Netvar {
+    /// Returns where the elements of an array NetVar are, `None` if it is not an array.
+    pub fn array_layout(&self) -> Option<ArrayLayout> {
+        if !matches!(self.prop_type, EPropType::Array | EPropType::DataTable)
+            || self.elements_count <= 0
+            || self.element_stride <= 0
+        {
+            return None;
+        }
+
+        Some(ArrayLayout {
+            offset: self.offset,
+            len: self.elements_count as usize,
+            stride: self.element_stride as usize,
+        })
+    }
+}
+
+/// The offset of an array NetVar's first element, its element count and the
+/// distance between two elements.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ArrayLayout {
+    pub offset: usize,
+    pub len: usize,
+    pub stride: usize,
+}
 
 /// Returns the NetVar value of the given NetVar.
+///
+/// Returns `None` if the NetVar does not exist or if the NetVars have not been scanned yet.
+/// # Examples
+/// ```
+/// use sdk::netvar::{self, dump::NetvarDump};
+///
+/// assert_eq!(netvar::get_offset("DT_BasePlayer", "m_iHealth"), None);
+///
+/// netvar::load_dump(&NetvarDump::parse("# netvar dump v2\nDT_BasePlayer\tm_iHealth\tInt\t0x100\t4\t0\t0\t0\tDT_BasePlayer")?);
+///
+/// assert_eq!(netvar::get_offset("DT_BasePlayer", "m_iHealth"), Some(0x100));
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+pub fn get_offset(table: &str, netvar: &str) -> Option<usize> {
+    NETVARS
+        .read()
+        .get(&(table.to_string() + "->" + netvar))
+        .map(|netvar| netvar.offset)
+}
+
+/// An offset that is looked up once and then reused until the NetVars are scanned again.
+///
+/// `netvar!` keeps one of these per accessor, so reading a NetVar only costs a
+/// lookup the first time it is read after a scan.
+/// # Examples
+/// ```
+/// use sdk::netvar::{get_offset, CachedOffset};
+///
+/// static HEALTH: CachedOffset = CachedOffset::new();
+///
+/// let offset = HEALTH.get(|| get_offset("DT_BasePlayer", "m_iHealth"));
+/// ```
+pub struct CachedOffset {
+    generation: AtomicUsize,
+    offset: AtomicUsize,
+}
+
+impl CachedOffset {
+    const MISSING: usize = usize::MAX;
+
+    pub const fn new() -> Self {
+        Self {
+            generation: AtomicUsize::new(0),
+            offset: AtomicUsize::new(Self::MISSING),
+        }
+    }
+
+    /// Returns the cached offset, calling `resolve` first if the NetVars changed
+    /// since it was last resolved.
+    #[inline]
+    pub fn get(&self, resolve: impl FnOnce() -> Option<usize>) -> Option<usize> {
+        let generation = GENERATION.load(Ordering::Acquire);
+
+        if self.generation.load(Ordering::Acquire) != generation {
+            self.offset
+                .store(resolve().unwrap_or(Self::MISSING), Ordering::Relaxed);
+            self.generation.store(generation, Ordering::Release);
+        }
+
+        match self.offset.load(Ordering::Relaxed) {
+            Self::MISSING => None,
+            offset => Some(offset),
+        }
+    }
+}
+
+impl Default for CachedOffset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the layout of the given array NetVar, e.g. `m_hMyWeapons`.
+///
+/// Returns `None` if the NetVar does not exist or is not an array.
+pub fn get_array(table: &str, netvar: &str) -> Option<ArrayLayout> {
+    NETVARS
+        .read()
+        .get(&(table.to_string() + "->" + netvar))
+        .and_then(Netvar::array_layout)
+}
+
+/// [`CachedOffset`] for array NetVars.
+pub struct CachedArray {
+    offset: CachedOffset,
+    len: AtomicUsize,
+    stride: AtomicUsize,
+}
+
+impl CachedArray {
+    pub const fn new() -> Self {
+        Self {
+            offset: CachedOffset::new(),
+            len: AtomicUsize::new(0),
+            stride: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the cached layout, calling `resolve` first if the NetVars changed
+    /// since it was last resolved.
+    #[inline]
+    pub fn get(&self, resolve: impl FnOnce() -> Option<ArrayLayout>) -> Option<ArrayLayout> {
+        let offset = self.offset.get(|| {
+            let layout = resolve()?;
+
+            self.len.store(layout.len, Ordering::Relaxed);
+            self.stride.store(layout.stride, Ordering::Relaxed);
+
+            Some(layout.offset)
+        })?;
+
+        Some(ArrayLayout {
+            offset,
+            len: self.len.load(Ordering::Relaxed),
+            stride: self.stride.load(Ordering::Relaxed),
+        })
+    }
+}
+
+impl Default for CachedArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The elements of an array NetVar, returned by `netvar!` accessors declared with `[T]`.
+///
+/// Reading or writing out of bounds is rejected instead of touching the memory behind the array.
+/// # Examples
+/// ```
+/// use sdk::netvar::{ArrayLayout, NetvarArray};
+///
+/// let handles = [1u32, 2, 3];
+/// let layout = ArrayLayout { offset: 0, len: 3, stride: 4 };
+/// let array = unsafe { NetvarArray::<u32>::new(handles.as_ptr() as usize, layout) };
+///
+/// assert_eq!(array.get(1), Some(2));
+/// assert_eq!(array.get(3), None);
+/// assert_eq!(array.iter().sum::<u32>(), 6);
+/// ```
+pub struct NetvarArray<T> {
+    address: usize,
+    len: usize,
+    stride: usize,
+    element: PhantomData<T>,
+}
+
+impl<T: Copy> NetvarArray<T> {
+    /// Creates the array described by `layout` for the entity at `base`.
+    ///
+    /// The array is empty if `base` is null or an element does not fit into the stride.
+    /// # Safety
+    /// `base + layout.offset` has to point to `layout.len` elements, `layout.stride` bytes apart.
+    pub unsafe fn new(base: usize, layout: ArrayLayout) -> Self {
+        if base == 0 || layout.stride < size_of::<T>() {
+            return Self::default();
+        }
+
+        Self {
+            address: base + layout.offset,
+            len: layout.len,
+            stride: layout.stride,
+            element: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(unsafe { ((self.address + index * self.stride) as *const T).read_unaligned() })
+    }
+
+    /// Overwrites the element at `index`.
+    pub fn set(&self, index: usize, value: T) -> Result<(), Error> {
+        if index >= self.len {
+            return Err(Error::Other {
+                msg: format!("index {} is out of bounds for {} elements", index, self.len),
+            });
+        }
+
+        unsafe { ((self.address + index * self.stride) as *mut T).write_unaligned(value) };
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len).filter_map(move |index| self.get(index))
+    }
+}
+
+impl<T> Default for NetvarArray<T> {
+    fn default() -> Self {
+        Self {
+            address: 0,
+            len: 0,
+            stride: 0,
+            element: PhantomData,
+        }
+    }
+}
+
+/// Invalidates every [`CachedOffset`], called whenever `NETVARS` changed.
+pub(crate) fn invalidate_offsets() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Replaces the stored NetVars with the ones from `dump`, without scanning the game.
+///
+/// This lets `netvar!` accessors run against plain memory, e.g. in tests or offline
+/// tools. NetVars loaded this way can't be hooked, since there is no `RecvProp` behind them.
+/// # Examples
+/// ```
+/// use sdk::classes::entity::CEntity;
+/// use sdk::interface::Interface;
+/// use sdk::netvar::{self, dump::NetvarDump};
+///
+/// let dump = NetvarDump::parse("# netvar dump v2\nDT_BasePlayer\tm_iHealth\tInt\t0x100\t4\t0\t0\t0\tDT_BasePlayer")?;
+/// netvar::load_dump(&dump);
+///
+/// let mut memory = [0u8; 0x200];
+/// memory[0x100..0x104].copy_from_slice(&100i32.to_le_bytes());
+///
+/// let player = unsafe { CEntity::from_raw_unchecked(memory.as_ptr() as *const usize) };
+///
+/// assert_eq!(player.get_health(), 100);
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+pub fn load_dump(dump: &dump::NetvarDump) {
+    *NETVARS.write() = dump.netvars.clone();
+
+    invalidate_offsets();
+}
+
+/// Returns all metadata stored for the given NetVar.
 /// # Examples
 /// ```
-/// use sdk::netvar;
+/// use sdk::netvar::{self, dump::NetvarDump};
+/// use sdk::definitions::recv_props::EPropType;
+///
+/// netvar::load_dump(&NetvarDump::parse("# netvar dump v2\nDT_BasePlayer\tm_iHealth\tInt\t0x100\t4\t0\t0\t0\tDT_BasePlayer")?);
 ///
-/// let offset = netvar::get_offset("DT_BasePlayer", "m_iHealth");
+/// let health = netvar::get_netvar("DT_BasePlayer", "m_iHealth").unwrap();
 ///
-/// assert_eq!(offset, 0x100);
+/// assert_eq!(health.prop_type, EPropType::Int);
+/// # Ok::<(), sdk::utils::error::Error>(())
 /// ```
-pub fn get_offset(table: &str, netvar: &str) -> usize {
-    *NETVARS
+pub fn get_netvar(table: &str, netvar: &str) -> Option<Netvar> {
+    NETVARS
         .read()
         .get(&(table.to_string() + "->" + netvar))
-        .unwrap_or(&0)
+        .cloned()
+}
+
+/// Returns every NetVar stored under `table`, e.g. `DT_CSPlayer`.
+pub fn get_table(table: &str) -> Vec<Netvar> {
+    let prefix = table.to_string() + "->";
+
+    NETVARS
+        .read()
+        .range(prefix.clone()..)
+        .take_while(|(key, _)| key.starts_with(&prefix))
+        .map(|(_, netvar)| netvar.clone())
+        .collect()
+}
+
+/// Returns every table that has a NetVar called `netvar`, together with the NetVar.
+pub fn find(netvar: &str) -> Vec<(String, Netvar)> {
+    NETVARS
+        .read()
+        .iter()
+        .filter(|(_, stored)| stored.name == netvar)
+        .map(|(key, stored)| {
+            (
+                key.split("->").next().unwrap_or("").to_string(),
+                stored.clone(),
+            )
+        })
+        .collect()
 }
 
 pub mod hook {
     //! Easily hook NetVars.
-    use crate::definitions::recv_props::{CRecvProp, RecvVarProxyFn};
+    //!
+    //! Every hooked `RecvProp` gets its own chain, so several [`RecvProxyHook`]s can
+    //! subscribe to the same NetVar. Subscribers run in the order they were added, each
+    //! seeing the value the previous one left behind, before the original proxy stores it.
+    use alloc::collections::BTreeMap;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use libc::c_void;
+    use static_init::dynamic;
+
+    use crate::definitions::recv_props::{CRecvProp, CRecvProxy, RecvVarProxyFn};
     use crate::netvar::PROPS;
 
+    type Subscriber = Arc<dyn Fn(&mut CRecvProxy, *mut c_void) + Send + Sync>;
+
+    struct Chain {
+        original: Option<RecvVarProxyFn>,
+        subscribers: Vec<(usize, Subscriber)>,
+    }
+
+    /// Hook chains keyed by the address of their `RecvProp`.
+    ///
+    /// A chain is removed together with its last hook, which restores the original proxy first.
+    #[dynamic]
+    static mut CHAINS: BTreeMap<usize, Chain> = BTreeMap::new();
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    /// The proxy of every hooked `RecvProp`.
+    ///
+    /// The chain is copied out before the subscribers run, so they can hook and unhook
+    /// NetVars without deadlocking.
+    unsafe extern "C" fn dispatch(data: *const CRecvProxy, entity: *mut c_void, out: *mut c_void) {
+        let property = (*data).recv_prop as usize;
+
+        let (original, subscribers) = match CHAINS.read().get(&property) {
+            Some(chain) => (
+                chain.original,
+                chain
+                    .subscribers
+                    .iter()
+                    .map(|(_, subscriber)| subscriber.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            None => {
+                // The last hook was dropped after this call entered, which put the original back.
+                let proxy_fn = (*(*data).recv_prop).proxy_fn;
+
+                (proxy_fn.filter(|_| !is_dispatch(proxy_fn)), Vec::new())
+            }
+        };
+
+        let mut data = *data;
+
+        for subscriber in &subscribers {
+            subscriber(&mut data, entity);
+        }
+
+        if let Some(original) = original {
+            original(&data, entity, out);
+        }
+    }
+
+    fn is_dispatch(proxy_fn: Option<RecvVarProxyFn>) -> bool {
+        proxy_fn.is_some_and(|proxy_fn| proxy_fn as usize == dispatch as RecvVarProxyFn as usize)
+    }
+
+    /// A subscription to a NetVar's proxy, which is removed again on drop.
+    ///
+    /// Once the last hook of a `RecvProp` is dropped, its original proxy is restored.
     pub struct RecvProxyHook {
         property: usize,
-        original: RecvVarProxyFn,
+        id: usize,
     }
 
     impl RecvProxyHook {
-        pub(crate) fn hook(property: usize, proxy_fn: RecvVarProxyFn) -> Self {
+        pub(crate) fn hook(property: usize, subscriber: Subscriber) -> Self {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
             let prop = unsafe { transmute!(property, *mut CRecvProp).as_mut().unwrap() };
 
-            let original = prop.proxy_fn;
+            let mut chains = CHAINS.write();
+            let chain = chains.entry(property).or_insert_with(|| Chain {
+                original: None,
+                subscribers: Vec::new(),
+            });
+
+            if !is_dispatch(prop.proxy_fn) {
+                chain.original = prop.proxy_fn;
+                prop.proxy_fn = Some(dispatch);
+            }
 
-            prop.proxy_fn = proxy_fn;
+            chain.subscribers.push((id, subscriber));
 
-            Self { property, original }
+            Self { property, id }
         }
 
-        pub fn reset(&mut self) {
-            unsafe {
-                let prop = transmute!(self.property, *mut CRecvProp).as_mut().unwrap();
-                prop.proxy_fn = self.original;
-            }
+        /// Returns the proxy that was installed before the first hook.
+        pub fn get_original(&self) -> Option<RecvVarProxyFn> {
+            CHAINS
+                .read()
+                .get(&self.property)
+                .and_then(|chain| chain.original)
         }
+    }
+
+    impl Drop for RecvProxyHook {
+        fn drop(&mut self) {
+            let mut chains = CHAINS.write();
+            let chain = some_or_ret!(chains.get_mut(&self.property));
+
+            chain.subscribers.retain(|(id, _)| *id != self.id);
+
+            if chain.subscribers.is_empty() {
+                let prop = unsafe { transmute!(self.property, *mut CRecvProp).as_mut().unwrap() };
 
-        pub fn get_original(&self) -> RecvVarProxyFn {
-            unsafe { transmute!(self.original, RecvVarProxyFn) }
+                if is_dispatch(prop.proxy_fn) {
+                    prop.proxy_fn = chain.original;
+                }
+
+                chains.remove(&self.property);
+            }
         }
     }
 
     /// Hook NetVars
     ///
+    /// The hook stays active until the returned [`RecvProxyHook`] is dropped.
     /// # Examples
     /// ```
     /// use sdk::definitions::recv_props::CRecvProxy;
     /// use sdk::netvar::hook::hook_netvar;
     /// use std::ffi::c_void;
     ///
-    /// // Thats how the hooked fn should look like
-    /// fn hook(proxy_data_const: *const CRecvProxy, entity: *mut c_void, output: *mut c_void) {
+    /// // Thats how the subscriber should look like, changes to `data.value` are what gets stored.
+    /// fn hook(data: &mut CRecvProxy, entity: *mut c_void) {
     /// // ...
     /// }
     ///
-    /// // You would store this globally to be able to call the original in the hooked fn.
+    /// // You would store this globally to keep the hook alive.
     /// let hook = hook_netvar("CBaseViewModel->m_nSequence", hook);
     /// ```
-    pub fn hook_netvar(name: &str, hook: RecvVarProxyFn) -> Option<RecvProxyHook> {
-        PROPS
-            .read()
-            .get(name)
-            .map(|prop| RecvProxyHook::hook(*prop, hook))
+    pub fn hook_netvar<F>(name: &str, subscriber: F) -> Option<RecvProxyHook>
+    where
+        F: Fn(&mut CRecvProxy, *mut c_void) + Send + Sync + 'static,
+    {
+        let property = *PROPS.read().get(name)?;
+
+        Some(RecvProxyHook::hook(property, Arc::new(subscriber)))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use core::mem::zeroed;
+        use core::ptr::null_mut;
+
+        use super::*;
+
+        unsafe extern "C" fn store(data: *const CRecvProxy, _: *mut c_void, out: *mut c_void) {
+            *(out as *mut i32) = (*data).value.data.int;
+        }
+
+        unsafe fn receive(prop: *mut CRecvProp, value: i32) -> i32 {
+            let mut data: CRecvProxy = zeroed();
+            data.recv_prop = prop;
+            data.value.data.int = value;
+
+            let mut out = 0;
+            (*prop).proxy_fn.unwrap()(&data, null_mut(), &mut out as *mut i32 as *mut c_void);
+
+            out
+        }
+
+        #[test]
+        fn hook_inside_subscriber() {
+            let mut prop: CRecvProp = unsafe { zeroed() };
+            prop.proxy_fn = Some(store);
+            let prop = &mut prop as *mut CRecvProp;
+            let property = prop as usize;
+
+            let hook = RecvProxyHook::hook(
+                property,
+                Arc::new(move |data, _| {
+                    drop(RecvProxyHook::hook(property, Arc::new(|_, _| {})));
+                    unsafe { data.value.data.int += 1 };
+                }),
+            );
+
+            assert_eq!(unsafe { receive(prop, 1) }, 2);
+            assert!(is_dispatch(unsafe { (*prop).proxy_fn }));
+
+            drop(hook);
+
+            assert_eq!(
+                unsafe { (*prop).proxy_fn }.map(|proxy_fn| proxy_fn as usize),
+                Some(store as RecvVarProxyFn as usize)
+            );
+            assert!(CHAINS.read().get(&property).is_none());
+            assert_eq!(unsafe { receive(prop, 1) }, 1);
+        }
     }
 }
 
 pub(crate) mod manager {
-    use crate::definitions::recv_props::{CRecvTable, EPropType};
-    use crate::interfaces::client::ClientClass;
-    use crate::netvar::{NETVARS, PROPS};
+    use crate::definitions::recv_props::{CRecvProp, CRecvTable, EPropType};
+    use crate::interfaces::client::{ClientClass, IClient};
+    use crate::netvar::dump::NetvarDump;
+    use crate::netvar::{classes, invalidate_offsets, Netvar, NETVARS, PROPS};
     use crate::utils::error::Error;
     use crate::utils::error::Error::{Null, Unknown};
+    use crate::utils::math::vector::{Vec2, Vec3};
+    use crate::utils::x86::{decode, Mode};
+    use alloc::collections::BTreeMap;
     use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use core::mem::size_of;
+    use core::slice;
     use cstr_core::CStr;
 
-    unsafe fn store_props(group_name: String, recv_table: *mut CRecvTable, child_offset: usize) {
+    /// How much of a proxy is decoded to find out how many bytes it stores.
+    const MAX_PROXY_LENGTH: usize = 0x40;
+
+    /// The NetVars, props and classes found by [`scan`], which are only used once they
+    /// are installed.
+    #[derive(Default)]
+    pub(crate) struct Scan {
+        pub(crate) netvars: NetvarDump,
+        props: BTreeMap<String, usize>,
+        classes: Vec<*const ClientClass>,
+    }
+
+    impl Scan {
+        /// Replaces the stored NetVars, props and classes with the scanned ones.
+        pub(crate) fn install(self) {
+            *NETVARS.write() = self.netvars.netvars;
+            *PROPS.write() = self.props;
+
+            classes::clear();
+
+            for class in self.classes {
+                unsafe { classes::register(class) };
+            }
+
+            invalidate_offsets();
+        }
+    }
+
+    /// Returns the offset, element count and stride of array props.
+    ///
+    /// `RecvPropArray` props have no offset of their own, their elements are described by
+    /// the preceding `name[0]` prop. `RecvPropArray3` props are data tables with one prop
+    /// per element, named `000`, `001` and so on.
+    unsafe fn array_metadata(
+        prop: &CRecvProp,
+        child: *mut CRecvTable,
+    ) -> Option<(usize, i32, i32)> {
+        if prop.prop_type == EPropType::Array {
+            let element = prop.array_prop.as_ref()?;
+
+            return Some((
+                element.offset as usize,
+                prop.elements_count,
+                prop.element_stride,
+            ));
+        }
+
+        if prop.prop_type != EPropType::DataTable || child.is_null() || (*child).n_props <= 0 {
+            return None;
+        }
+
+        let first = (*child).p_props.read();
+
+        if !CStr::from_ptr(first.prop_name)
+            .to_bytes()
+            .first()?
+            .is_ascii_digit()
+        {
+            return None;
+        }
+
+        // A single element has no neighbour to measure the distance to, it is as large as
+        // the element itself. `Int` elements with a custom proxy are whole `int`s.
+        let stride = match ((*child).n_props, value_size(&first)) {
+            (1, 0) if first.prop_type == EPropType::Int => size_of::<i32>() as i32,
+            (1, size) => size as i32,
+            _ => (*child).p_props.offset(1).read().offset - first.offset,
+        };
+
+        Some((
+            (prop.offset + first.offset) as usize,
+            (*child).n_props,
+            stride,
+        ))
+    }
+
+    /// Returns how many bytes the value of `prop` takes up in the entity, `0` if unknown.
+    unsafe fn value_size(prop: &CRecvProp) -> usize {
+        match prop.prop_type {
+            EPropType::Int => prop.proxy_fn.map_or(0, |proxy| {
+                let mode = if cfg!(target_pointer_width = "64") {
+                    Mode::X64
+                } else {
+                    Mode::X86
+                };
+
+                store_size(
+                    slice::from_raw_parts(proxy as *const u8, MAX_PROXY_LENGTH),
+                    mode,
+                )
+                .unwrap_or(0)
+            }),
+            EPropType::Float => size_of::<f32>(),
+            EPropType::Vec => size_of::<Vec3>(),
+            EPropType::VecXY => size_of::<Vec2>(),
+            EPropType::Int64 => size_of::<i64>(),
+            EPropType::String => prop.buffer_size.max(0) as usize,
+            EPropType::Array | EPropType::DataTable => 0,
+        }
+    }
+
+    /// Returns how many bytes the proxy starting at `code` stores.
+    ///
+    /// `RecvPropInt` picks `RecvProxy_Int32ToInt8`, `16` or `32` by the size of the field,
+    /// each of which only copies the value with a single `mov`. `None` if the proxy branches,
+    /// calls or stores more than once, i.e. is a custom one.
+    pub(super) fn store_size(code: &[u8], mode: Mode) -> Option<usize> {
+        let mut position = 0;
+        let mut size = None;
+
+        while position < code.len() {
+            let instruction = decode(&code[position..], mode)?;
+            let bytes = &code[position..position + instruction.length];
+            position += instruction.length;
+
+            let prefixes = bytes
+                .iter()
+                .take_while(|byte| {
+                    matches!(
+                        byte,
+                        0x66 | 0x67 | 0xF0 | 0xF2 | 0xF3 | 0x2E | 0x36 | 0x3E | 0x26 | 0x64 | 0x65
+                    )
+                })
+                .count();
+            let operand_size_override = bytes[..prefixes].contains(&0x66);
+            let (rex_w, opcode) = match &bytes[prefixes..] {
+                [rex @ 0x40..=0x4F, opcode @ ..] if mode == Mode::X64 => (rex & 0x08 != 0, opcode),
+                opcode => (false, opcode),
+            };
+
+            let stored = match opcode {
+                [0xC2 | 0xC3, ..] => return size,
+                // `mov r/m8, r8` and `mov r/m8, imm8` to memory.
+                [0x88 | 0xC6, modrm, ..] if modrm >> 6 != 3 => 1,
+                [0x89 | 0xC7, modrm, ..] if modrm >> 6 != 3 => {
+                    if rex_w {
+                        return None;
+                    }
+
+                    if operand_size_override {
+                        2
+                    } else {
+                        4
+                    }
+                }
+                [0x70..=0x7F | 0xE8 | 0xE9 | 0xEB | 0xFF, ..] | [0x0F, 0x80..=0x8F, ..] => {
+                    return None
+                }
+                _ => continue,
+            };
+
+            if size.replace(stored).is_some() {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    unsafe fn store_props(
+        scan: &mut Scan,
+        group_name: String,
+        recv_table: *mut CRecvTable,
+        child_offset: usize,
+    ) {
+        let owner_name = CStr::from_ptr((*recv_table).table_name)
+            .to_str()
+            .unwrap_or("?")
+            .to_string();
+
         for i in 0..(*recv_table).n_props as isize {
             let prop = (*recv_table).p_props.offset(i).read();
             let child = prop.data_table;
@@ -116,6 +777,7 @@ pub(crate) mod manager {
 
                 if prop.prop_type == EPropType::DataTable && table_name.starts_with('D') {
                     store_props(
+                        scan,
                         group_name.to_string(),
                         child,
                         prop.offset as usize + child_offset,
@@ -123,22 +785,37 @@ pub(crate) mod manager {
                 }
             }
 
+            let (offset, elements_count, element_stride) =
+                array_metadata(&prop, child).unwrap_or((
+                    prop.offset as usize,
+                    prop.elements_count,
+                    prop.element_stride,
+                ));
+
             let formatted = format!("{}->{}", group_name, var_name);
 
-            PROPS
-                .write()
+            scan.props
                 .insert(formatted.clone(), (*recv_table).p_props.offset(i) as usize);
 
-            NETVARS.write().insert(
+            scan.netvars.netvars.insert(
                 formatted.replacen("C", "DT_", 1),
-                prop.offset as usize + child_offset as usize,
+                Netvar {
+                    table: owner_name.clone(),
+                    name: var_name,
+                    prop_type: prop.prop_type,
+                    flags: prop.prop_flags,
+                    offset: offset + child_offset,
+                    size: value_size(&prop),
+                    elements_count,
+                    element_stride,
+                },
             );
         }
     }
 
-    /// Loads all NetVar's, this is used in sdk::initialize only.
-    pub(crate) fn scan() -> Result<(), Error> {
-        let mut client_class_ptr = crate::get_interfaces().client.get_all_classes();
+    /// Scans all NetVar's without installing them, this is used in sdk::initialize only.
+    pub(crate) fn scan(client: &IClient) -> Result<Scan, Error> {
+        let mut client_class_ptr = client.get_all_classes();
 
         if client_class_ptr.is_null() {
             return Err(Null {
@@ -146,8 +823,12 @@ pub(crate) mod manager {
             });
         }
 
+        let mut scan = Scan::default();
+
         while !client_class_ptr.is_null() {
             unsafe {
+                scan.classes.push(client_class_ptr);
+
                 let recv_table = client_class_ptr.read().recv_table;
 
                 let table_name = CStr::from_ptr(client_class_ptr.read().network_name)
@@ -155,18 +836,56 @@ pub(crate) mod manager {
                     .unwrap()
                     .to_string();
 
-                store_props(table_name, recv_table, 0);
+                store_props(&mut scan, table_name, recv_table, 0);
 
                 client_class_ptr = client_class_ptr.read().next as *const ClientClass;
             }
         }
 
-        if NETVARS.read().len() == 0 {
+        if scan.netvars.netvars.is_empty() {
             return Err(Unknown {
                 message: obfstr!("Failed to scan NetVars.").into(),
             });
         }
 
-        Ok(())
+        Ok(scan)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::store_size;
+        use crate::utils::x86::Mode;
+
+        #[test]
+        fn int_proxies() {
+            // mov eax, [rdi + 8]; mov [rdx], al; ret
+            assert_eq!(
+                store_size(&[0x8B, 0x47, 0x08, 0x88, 0x02, 0xC3], Mode::X64),
+                Some(1)
+            );
+            // mov eax, [rdi + 8]; mov [rdx], ax; ret
+            assert_eq!(
+                store_size(&[0x8B, 0x47, 0x08, 0x66, 0x89, 0x02, 0xC3], Mode::X64),
+                Some(2)
+            );
+            // mov eax, [esp + 4]; mov ecx, [esp + 0xC]; mov eax, [eax + 8]; mov [ecx], eax; ret
+            let int32 = [
+                0x8B, 0x44, 0x24, 0x04, 0x8B, 0x4C, 0x24, 0x0C, 0x8B, 0x40, 0x08, 0x89, 0x01, 0xC3,
+            ];
+            assert_eq!(store_size(&int32, Mode::X86), Some(4));
+        }
+
+        #[test]
+        fn custom_proxies() {
+            // jmp rel32
+            assert_eq!(store_size(&[0xE9, 0, 0, 0, 0], Mode::X64), None);
+            // mov [rdx], rax; ret
+            assert_eq!(store_size(&[0x48, 0x89, 0x02, 0xC3], Mode::X64), None);
+            // mov [rdx], al; mov [rdx + 1], al; ret
+            assert_eq!(
+                store_size(&[0x88, 0x02, 0x88, 0x42, 0x01, 0xC3], Mode::X64),
+                None
+            );
+        }
     }
 }
@@ -0,0 +1,136 @@
+//! Networked classes by their class id.
+//!
+//! The registry is filled by the NetVar scan, classes that show up later are added
+//! the first time an entity of them is looked up.
+//! # Examples
+//! ```no_run
+//! use sdk::netvar::classes;
+//!
+//! let planted_c4 = classes::find("CPlantedC4").unwrap();
+//!
+//! println!("{} has class id {}", planted_c4.network_name, planted_c4.class_id);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use cstr_core::CStr;
+use static_init::dynamic;
+
+use crate::definitions::recv_props::{CRecvTable, EPropType};
+use crate::interfaces::client::ClientClass;
+
+#[dynamic]
+static mut CLASSES: BTreeMap<i32, NetworkClass> = BTreeMap::new();
+
+/// A `ClientClass` and the tables its `RecvTable` inherits from.
+#[derive(Clone, Debug)]
+pub struct NetworkClass {
+    pub class_id: i32,
+    /// The network name, e.g. `CCSPlayer`.
+    pub network_name: String,
+    /// The class' own table followed by its base tables, e.g. `DT_CSPlayer`,
+    /// `DT_BasePlayer`, ..., `DT_BaseEntity`.
+    pub tables: Vec<String>,
+}
+
+impl NetworkClass {
+    /// Returns `true` if the class' `RecvTable` is or inherits from `table`.
+    pub fn is_a(&self, table: &str) -> bool {
+        self.tables.iter().any(|name| name == table)
+    }
+
+    unsafe fn from_client_class(class: &ClientClass) -> Self {
+        let mut tables = Vec::new();
+        let mut recv_table = class.recv_table;
+
+        while !recv_table.is_null() {
+            tables.push(table_name(recv_table));
+            recv_table = base_table(recv_table);
+        }
+
+        Self {
+            class_id: class.class_id,
+            network_name: CStr::from_ptr(class.network_name)
+                .to_str()
+                .unwrap_or("?")
+                .to_string(),
+            tables,
+        }
+    }
+}
+
+unsafe fn table_name(recv_table: *const CRecvTable) -> String {
+    CStr::from_ptr((*recv_table).table_name)
+        .to_str()
+        .unwrap_or("?")
+        .to_string()
+}
+
+/// Returns the table of the `baseclass` prop, if there is one.
+unsafe fn base_table(recv_table: *const CRecvTable) -> *mut CRecvTable {
+    for i in 0..(*recv_table).n_props as isize {
+        let prop = (*recv_table).p_props.offset(i).read();
+
+        if prop.prop_type == EPropType::DataTable
+            && CStr::from_ptr(prop.prop_name).to_bytes() == obfstr!("baseclass").as_bytes()
+        {
+            return prop.data_table;
+        }
+    }
+
+    core::ptr::null_mut()
+}
+
+/// Returns the class with the id `class_id`.
+pub fn get(class_id: i32) -> Option<NetworkClass> {
+    CLASSES.read().get(&class_id).cloned()
+}
+
+/// Returns the class called `network_name`, e.g. `CSmokeGrenadeProjectile`.
+pub fn find(network_name: &str) -> Option<NetworkClass> {
+    CLASSES
+        .read()
+        .values()
+        .find(|class| class.network_name == network_name)
+        .cloned()
+}
+
+/// Returns every known class, ordered by class id.
+pub fn all() -> Vec<NetworkClass> {
+    CLASSES.read().values().cloned().collect()
+}
+
+/// Adds `class` to the registry, unless a class with its id is already known.
+pub(crate) unsafe fn register(class: *const ClientClass) {
+    let class = some_or_ret!(class.as_ref());
+
+    CLASSES
+        .write()
+        .entry(class.class_id)
+        .or_insert_with(|| NetworkClass::from_client_class(class));
+}
+
+/// Forgets every class, called before the NetVars are scanned again.
+pub(crate) fn clear() {
+    CLASSES.write().clear();
+}
+
+/// Calls `predicate` with the registered class of `class`, registering it first if needed.
+///
+/// Returns `false` if `class` is null.
+pub(crate) fn matches(
+    class: *const ClientClass,
+    predicate: impl FnOnce(&NetworkClass) -> bool,
+) -> bool {
+    let class_id = some_or_ret!(unsafe { class.as_ref() }, false).class_id;
+
+    if let Some(known) = CLASSES.read().get(&class_id) {
+        return predicate(known);
+    }
+
+    unsafe { register(class) };
+
+    CLASSES.read().get(&class_id).is_some_and(predicate)
+}
@@ -0,0 +1,270 @@
+//! Indexes the fields of entity datamaps (`datamap_t`) by name.
+//!
+//! Many useful fields are not networked, but most of them are described in the
+//! data or prediction descriptor tables every entity class has. Unlike `RecvTable`s
+//! these can only be reached through an entity, so they are indexed lazily the
+//! first time an entity of a class is looked at.
+//! # Examples
+//! ```no_run
+//! use sdk::netvar::datamap;
+//!
+//! let player = sdk::get_local_player();
+//!
+//! let offset = datamap::find_offset(&player, "m_nNextThinkTick");
+//! ```
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use cstr_core::CStr;
+use static_init::dynamic;
+
+use crate::classes::entity::CEntity;
+use crate::definitions::datamap::{DataMap, FIELD_EMBEDDED};
+use crate::netvar::dump::NetvarDump;
+use crate::utils::error::Error;
+
+#[dynamic]
+static mut DATAMAPS: BTreeMap<String, BTreeMap<String, DataField>> = BTreeMap::new();
+#[dynamic]
+static mut INDEXED: BTreeSet<usize> = BTreeSet::new();
+
+/// A field described by a datamap.
+#[derive(Clone, Debug)]
+pub struct DataField {
+    /// The datamap the field is declared in, e.g. `C_BaseEntity`.
+    pub class: String,
+    /// The field name, fields of embedded datamaps are joined with a `.`, e.g. `m_Collision.m_vecMins`.
+    pub name: String,
+    /// The raw `fieldtype_t`.
+    pub field_type: i32,
+    /// Offset from the start of the entity.
+    pub offset: usize,
+    pub size_in_bytes: i32,
+    pub flags: i16,
+}
+
+/// Indexes `map` including all of its base maps and returns the name it is stored under.
+///
+/// Maps that were already indexed are skipped. Returns `None` if `map` is `null()`.
+/// # Safety
+/// `map` has to point to a valid `datamap_t`.
+pub unsafe fn index(map: *const DataMap) -> Option<String> {
+    if map.is_null() {
+        return None;
+    }
+
+    let class = c_str((*map).data_class_name);
+
+    if !INDEXED.write().insert(map as usize) {
+        return Some(class);
+    }
+
+    let mut fields = BTreeMap::new();
+    let mut current = map;
+
+    while !current.is_null() {
+        collect(current, 0, "", &mut fields);
+        current = (*current).base_map;
+    }
+
+    // Data and prediction maps share their class name, so merge them.
+    DATAMAPS
+        .write()
+        .entry(class.clone())
+        .or_default()
+        .extend(fields);
+
+    Some(class)
+}
+
+unsafe fn collect(
+    map: *const DataMap,
+    base_offset: usize,
+    prefix: &str,
+    fields: &mut BTreeMap<String, DataField>,
+) {
+    let class = c_str((*map).data_class_name);
+
+    for i in 0..(*map).data_num_fields as isize {
+        let description = (*map).data_desc.offset(i);
+
+        if (*description).field_name.is_null() {
+            continue;
+        }
+
+        let name = format!("{}{}", prefix, c_str((*description).field_name));
+        let offset = base_offset + (*description).field_offset as usize;
+
+        if (*description).field_type == FIELD_EMBEDDED && !(*description).data_map.is_null() {
+            collect(
+                (*description).data_map,
+                offset,
+                &format!("{}.", name),
+                fields,
+            );
+        }
+
+        // Fields of derived maps come first and win over base map fields of the same name.
+        fields.entry(name.clone()).or_insert(DataField {
+            class: class.clone(),
+            name,
+            field_type: (*description).field_type,
+            offset,
+            size_in_bytes: (*description).field_size_in_bytes,
+            flags: (*description).flags,
+        });
+    }
+}
+
+/// Indexes the data and prediction descriptor maps of `entity` and returns their class names.
+pub fn index_entity(entity: &CEntity) -> Vec<String> {
+    if entity.is_null() {
+        return Vec::new();
+    }
+
+    unsafe {
+        [entity.get_data_desc_map(), entity.get_pred_desc_map()]
+            .iter()
+            .filter_map(|map| index(*map))
+            .collect()
+    }
+}
+
+/// Returns the field `field` of the datamap class `class`, e.g. `C_CSPlayer`.
+///
+/// Only classes that were indexed before are known.
+pub fn get_field(class: &str, field: &str) -> Option<DataField> {
+    DATAMAPS.read().get(class)?.get(field).cloned()
+}
+
+/// Returns the offset of `field` in the datamap class `class`.
+pub fn get_offset(class: &str, field: &str) -> Option<usize> {
+    get_field(class, field).map(|field| field.offset)
+}
+
+/// Looks `field` up in the datamaps of `entity`, indexing them first if necessary.
+pub fn find_offset(entity: &CEntity, field: &str) -> Option<usize> {
+    index_entity(entity)
+        .iter()
+        .find_map(|class| get_offset(class, field))
+}
+
+/// A non-networked field an accessor reads, e.g. `CEntity::get_glow_index`.
+///
+/// Before datamaps were indexed such fields were found at a fixed distance from a
+/// NetVar, which is still used if the datamaps don't describe the field.
+#[derive(Clone, Debug)]
+pub struct DataFieldDecl {
+    pub accessor: &'static str,
+    /// Name of the field in the datamaps, `None` if no datamap describes it.
+    pub field: Option<String>,
+    pub table: String,
+    pub netvar: String,
+    /// Distance from the NetVar to the field.
+    pub delta: usize,
+}
+
+impl DataFieldDecl {
+    /// Returns the offset of the field in `entity`.
+    pub fn resolve(&self, entity: &CEntity) -> Option<usize> {
+        self.field
+            .as_ref()
+            .and_then(|field| find_offset(entity, field))
+            .or_else(|| Some(crate::netvar::get_offset(&self.table, &self.netvar)? + self.delta))
+    }
+
+    /// Checks the declaration against the datamaps of `entity` and the NetVars of `dump`.
+    ///
+    /// Fails if the field has a name but isn't in the datamaps, even if the offset from
+    /// the NetVar can still be used, or if `entity` is `None`, since only an entity leads
+    /// to its datamaps.
+    pub fn check(
+        &self,
+        entity: Option<&CEntity>,
+        dump: &NetvarDump,
+    ) -> Result<(String, usize), Error> {
+        let fallback = format!("{}->{} + {:#X}", self.table, self.netvar, self.delta);
+
+        let field = match &self.field {
+            Some(field) => field,
+            None => {
+                let netvar =
+                    dump.get(&self.table, &self.netvar)
+                        .ok_or_else(|| Error::NotFound {
+                            item: format!("{}->{}", self.table, self.netvar),
+                        })?;
+
+                return Ok((fallback, netvar.offset + self.delta));
+            }
+        };
+
+        let classes = entity.map(index_entity).unwrap_or_default();
+
+        if classes.is_empty() {
+            return Err(Error::Other {
+                msg: format!(
+                    "no entity to index the datamaps of, {} is read at {}",
+                    field, fallback
+                ),
+            });
+        }
+
+        classes
+            .iter()
+            .find_map(|class| get_field(class, field))
+            .map(|data_field| (format!("{}.{}", data_field.class, field), data_field.offset))
+            .ok_or_else(|| Error::Other {
+                msg: format!(
+                    "{} is not in the datamaps of {}, it is read at {}",
+                    field,
+                    classes.join(", "),
+                    fallback
+                ),
+            })
+    }
+}
+
+unsafe fn c_str(ptr: *const libc::c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    CStr::from_ptr(ptr).to_str().unwrap_or("?").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declaration(field: Option<&str>) -> DataFieldDecl {
+        DataFieldDecl {
+            accessor: "get_bone_pos",
+            field: field.map(Into::into),
+            table: "DT_BaseAnimating".into(),
+            netvar: "m_nForceBone".into(),
+            delta: 0x1C,
+        }
+    }
+
+    #[test]
+    fn check_fallbacks() {
+        let dump = NetvarDump::parse(
+            "# netvar dump v2\nDT_BaseAnimating\tm_nForceBone\tInt\t0x268C\t4\t0\t0\t0\tDT_BaseAnimating",
+        )
+        .unwrap();
+
+        let (name, offset) = declaration(None).check(None, &dump).unwrap();
+        assert_eq!(name, "DT_BaseAnimating->m_nForceBone + 0x1C");
+        assert_eq!(offset, 0x26A8);
+
+        assert!(declaration(None)
+            .check(None, &NetvarDump::default())
+            .is_err());
+        // Without an entity the datamaps can't be checked, so the named field is reported.
+        assert!(declaration(Some("m_BoneAccessor"))
+            .check(None, &dump)
+            .is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//! Compares two [`NetvarDump`]s, e.g. from before and after a game update.
+//! # Examples
+//! ```no_run
+//! use sdk::netvar::diff::diff;
+//! use sdk::netvar::dump::NetvarDump;
+//!
+//! let old = NetvarDump::parse(&std::fs::read_to_string("old.txt").unwrap())?;
+//! let new = NetvarDump::capture();
+//! let changes = diff(&old, &new);
+//!
+//! for change in &changes.changes {
+//!     println!("{}", change);
+//! }
+//!
+//! for (declaration, change) in changes.affected() {
+//!     println!("{} is affected by {}", declaration, change);
+//! }
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::definitions::recv_props::EPropType;
+use crate::netvar::dump::NetvarDump;
+use crate::netvar::registry::{self, NetvarDecl};
+
+/// What happened to a single prop.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Added { offset: usize, prop_type: EPropType },
+    Removed { offset: usize, prop_type: EPropType },
+    Moved { old: usize, new: usize },
+    TypeChanged { old: EPropType, new: EPropType },
+}
+
+#[derive(Clone, Debug)]
+pub struct PropChange {
+    /// The table as used by `netvar!`, e.g. `DT_CSPlayer`.
+    pub table: String,
+    pub name: String,
+    pub change: Change,
+}
+
+/// All changes between two dumps, ordered by table and prop name.
+#[derive(Clone, Debug, Default)]
+pub struct DumpDiff {
+    pub changes: Vec<PropChange>,
+}
+
+impl DumpDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the changes of a single table.
+    pub fn table<'a>(&'a self, table: &'a str) -> impl Iterator<Item = &'a PropChange> {
+        self.changes
+            .iter()
+            .filter(move |change| change.table == table)
+    }
+
+    /// Returns every `netvar!` accessor of this crate that reads a prop that was removed,
+    /// moved or changed its type, together with the change.
+    pub fn affected(&self) -> Vec<(NetvarDecl, &PropChange)> {
+        let declared = registry::declared();
+
+        self.changes
+            .iter()
+            .filter(|change| !matches!(change.change, Change::Added { .. }))
+            .flat_map(|change| {
+                declared
+                    .iter()
+                    .filter(move |declaration| {
+                        declaration.table == change.table && declaration.name == change.name
+                    })
+                    .map(move |declaration| (declaration.clone(), change))
+            })
+            .collect()
+    }
+}
+
+/// Returns every prop that was added, removed, moved or changed its type from `old` to `new`.
+///
+/// A prop that moved and changed its type is listed twice.
+pub fn diff(old: &NetvarDump, new: &NetvarDump) -> DumpDiff {
+    let mut changes = Vec::new();
+
+    for (table, netvar) in old.iter() {
+        let change = match new.get(table, &netvar.name) {
+            None => Change::Removed {
+                offset: netvar.offset,
+                prop_type: netvar.prop_type,
+            },
+            Some(current) => {
+                if current.offset != netvar.offset {
+                    changes.push(PropChange {
+                        table: table.into(),
+                        name: netvar.name.clone(),
+                        change: Change::Moved {
+                            old: netvar.offset,
+                            new: current.offset,
+                        },
+                    });
+                }
+
+                if current.prop_type == netvar.prop_type {
+                    continue;
+                }
+
+                Change::TypeChanged {
+                    old: netvar.prop_type,
+                    new: current.prop_type,
+                }
+            }
+        };
+
+        changes.push(PropChange {
+            table: table.into(),
+            name: netvar.name.clone(),
+            change,
+        });
+    }
+
+    for (table, netvar) in new.iter() {
+        if old.get(table, &netvar.name).is_none() {
+            changes.push(PropChange {
+                table: table.into(),
+                name: netvar.name.clone(),
+                change: Change::Added {
+                    offset: netvar.offset,
+                    prop_type: netvar.prop_type,
+                },
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| (&a.table, &a.name).cmp(&(&b.table, &b.name)));
+
+    DumpDiff { changes }
+}
+
+impl fmt::Display for PropChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}->{}: ", self.table, self.name)?;
+
+        match &self.change {
+            Change::Added { offset, prop_type } => {
+                write!(f, "added at 0x{:X} ({})", offset, prop_type.name())
+            }
+            Change::Removed { offset, prop_type } => {
+                write!(f, "removed from 0x{:X} ({})", offset, prop_type.name())
+            }
+            Change::Moved { old, new } => write!(f, "moved from 0x{:X} to 0x{:X}", old, new),
+            Change::TypeChanged { old, new } => {
+                write!(f, "type changed from {} to {}", old.name(), new.name())
+            }
+        }
+    }
+}
+
+impl fmt::Display for DumpDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = None;
+
+        for change in &self.changes {
+            if table != Some(&change.table) {
+                writeln!(f, "{}", change.table)?;
+                table = Some(&change.table);
+            }
+
+            writeln!(f, "  {}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::netvar::Netvar;
+
+    fn dump(netvars: &[(&str, EPropType, usize)]) -> NetvarDump {
+        table_dump("DT_CSPlayer", netvars)
+    }
+
+    fn table_dump(table: &str, netvars: &[(&str, EPropType, usize)]) -> NetvarDump {
+        let mut dump = NetvarDump::default();
+
+        for (name, prop_type, offset) in netvars {
+            dump.netvars.insert(
+                format!("{}->{}", table, name),
+                Netvar {
+                    table: table.into(),
+                    name: name.to_string(),
+                    prop_type: *prop_type,
+                    flags: 0,
+                    offset: *offset,
+                    size: 4,
+                    elements_count: 1,
+                    element_stride: 0,
+                },
+            );
+        }
+
+        dump
+    }
+
+    #[test]
+    fn changes() {
+        let old = dump(&[
+            ("m_ArmorValue", EPropType::Int, 0xB378),
+            ("m_bHasHelmet", EPropType::Int, 0xB36C),
+            ("m_flFlashMaxAlpha", EPropType::Float, 0xA41C),
+            ("m_iHealth", EPropType::Int, 0x100),
+            ("m_szLastPlaceName", EPropType::String, 0x35C4),
+        ]);
+        let new = dump(&[
+            ("m_ArmorValue", EPropType::Int, 0xB37C),
+            ("m_bHasDefuser", EPropType::Int, 0xB388),
+            ("m_bHasHelmet", EPropType::Float, 0xB370),
+            ("m_flFlashMaxAlpha", EPropType::Int, 0xA41C),
+            ("m_iHealth", EPropType::Int, 0x100),
+        ]);
+
+        let changes = diff(&old, &new)
+            .changes
+            .into_iter()
+            .map(|change| (change.name, change.change))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "m_ArmorValue".into(),
+                    Change::Moved {
+                        old: 0xB378,
+                        new: 0xB37C
+                    }
+                ),
+                (
+                    "m_bHasDefuser".into(),
+                    Change::Added {
+                        offset: 0xB388,
+                        prop_type: EPropType::Int
+                    }
+                ),
+                (
+                    "m_bHasHelmet".into(),
+                    Change::Moved {
+                        old: 0xB36C,
+                        new: 0xB370
+                    }
+                ),
+                (
+                    "m_bHasHelmet".into(),
+                    Change::TypeChanged {
+                        old: EPropType::Int,
+                        new: EPropType::Float
+                    }
+                ),
+                (
+                    "m_flFlashMaxAlpha".into(),
+                    Change::TypeChanged {
+                        old: EPropType::Float,
+                        new: EPropType::Int
+                    }
+                ),
+                (
+                    "m_szLastPlaceName".into(),
+                    Change::Removed {
+                        offset: 0x35C4,
+                        prop_type: EPropType::String
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        let old = dump(&[("m_iHealth", EPropType::Int, 0x100)]);
+        let new = dump(&[("m_iHealth", EPropType::Int, 0x104)]);
+
+        assert!(diff(&old, &old).is_empty());
+        assert_eq!(
+            diff(&old, &new).to_string(),
+            "DT_CSPlayer\n  DT_CSPlayer->m_iHealth: moved from 0x100 to 0x104\n"
+        );
+    }
+
+    #[test]
+    fn affected_accessors() {
+        let old = table_dump(
+            "DT_BasePlayer",
+            &[
+                ("m_iHealth", EPropType::Int, 0x100),
+                ("m_lifeState", EPropType::Int, 0x25F),
+            ],
+        );
+        let new = table_dump(
+            "DT_BasePlayer",
+            &[
+                ("m_iHealth", EPropType::Int, 0x104),
+                ("m_iNewProp", EPropType::Int, 0x108),
+                ("m_lifeState", EPropType::Int, 0x25F),
+            ],
+        );
+
+        let changes = diff(&old, &new);
+        let affected = changes.affected();
+
+        assert_eq!(changes.changes.len(), 2);
+        assert!(!affected.is_empty());
+        assert!(affected.iter().all(|(declaration, change)| {
+            declaration.table == "DT_BasePlayer"
+                && declaration.name == "m_iHealth"
+                && change.change
+                    == Change::Moved {
+                        old: 0x100,
+                        new: 0x104,
+                    }
+        }));
+        assert!(affected
+            .iter()
+            .any(|(declaration, _)| declaration.accessor == "get_health"));
+    }
+}
@@ -0,0 +1,645 @@
+//! Dumps the networked class layout.
+//!
+//! [`NetvarTree::capture`] walks the same `ClientClass` -> `RecvTable` -> `RecvProp` tree as the
+//! NetVar manager, but keeps every nested table instead of flattening them.
+//! # Examples
+//! ```no_run
+//! use sdk::netvar::dump::NetvarTree;
+//!
+//! let tree = NetvarTree::capture(&sdk::get_interfaces().client)?;
+//!
+//! std::fs::write("netvars.json", tree.to_json()).unwrap();
+//! std::fs::write("netvars.toml", tree.to_toml()).unwrap();
+//! std::fs::write("netvars.h", tree.to_header()).unwrap();
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+//!
+//! [`NetvarDump`] on the other hand is the flattened view `netvar::get_offset` works with,
+//! in a line based format meant to be stored and compared with [`crate::netvar::diff`].
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use cstr_core::CStr;
+
+use crate::definitions::recv_props::{CRecvTable, EPropType};
+use crate::interfaces::client::{ClientClass, IClient};
+use crate::netvar::{Netvar, NETVARS};
+use crate::utils::error::Error;
+
+const DUMP_HEADER: &str = "# netvar dump v2";
+
+/// The scanned NetVars, keyed like `netvar::get_offset` (`DT_CSPlayer->m_iHealth`).
+/// # Examples
+/// ```no_run
+/// use sdk::netvar::dump::NetvarDump;
+///
+/// NetvarDump::capture().save("netvars.txt")?;
+///
+/// let dump = NetvarDump::load("netvars.txt")?;
+/// sdk::netvar::load_dump(&dump);
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct NetvarDump {
+    pub netvars: BTreeMap<String, Netvar>,
+}
+
+impl NetvarDump {
+    /// Copies the NetVars found by the last scan.
+    pub fn capture() -> Self {
+        Self {
+            netvars: (*NETVARS.read()).clone(),
+        }
+    }
+
+    /// Returns the NetVar `netvar` of `table`.
+    pub fn get(&self, table: &str, netvar: &str) -> Option<&Netvar> {
+        self.netvars.get(&(table.to_string() + "->" + netvar))
+    }
+
+    /// Iterates over `(table, netvar)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Netvar)> {
+        self.netvars
+            .iter()
+            .map(|(key, netvar)| (key.split("->").next().unwrap_or(""), netvar))
+    }
+
+    /// Parses a dump written by `to_string`.
+    ///
+    /// The first line has to be the header of this version of the format. Every other line
+    /// holds the table, the name, the type, the offset, the size, the element count, the
+    /// element stride, the flags and the declaring table, separated by tabs.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut netvars = BTreeMap::new();
+
+        match text.lines().next().map(|line| line.trim_end_matches('\r')) {
+            Some(DUMP_HEADER) => {}
+            Some(header) if header.starts_with("# netvar dump") => {
+                return Err(Error::Other {
+                    msg: format!("unsupported netvar dump version: {}", header),
+                })
+            }
+            _ => {
+                return Err(Error::Other {
+                    msg: format!("netvar dump doesn't start with \"{}\"", DUMP_HEADER),
+                })
+            }
+        }
+
+        for (number, line) in text.lines().enumerate().skip(1) {
+            let line = line.trim_end_matches('\r');
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || Error::Other {
+                msg: format!("invalid netvar dump line {}: {}", number + 1, line),
+            };
+
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            if fields.len() != 9 {
+                return Err(invalid());
+            }
+
+            let netvar = Netvar {
+                table: fields[8].to_string(),
+                name: fields[1].to_string(),
+                prop_type: EPropType::from_name(fields[2]).ok_or_else(invalid)?,
+                offset: parse_hex(fields[3]).ok_or_else(invalid)?,
+                size: fields[4].parse().map_err(|_| invalid())?,
+                elements_count: fields[5].parse().map_err(|_| invalid())?,
+                element_stride: fields[6].parse().map_err(|_| invalid())?,
+                flags: fields[7].parse().map_err(|_| invalid())?,
+            };
+
+            netvars.insert(format!("{}->{}", fields[0], fields[1]), netvar);
+        }
+
+        Ok(Self { netvars })
+    }
+
+    /// Reads a dump from `path`, e.g. a cache written by [`NetvarDump::save`].
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Writes the dump to `path`, so it can be loaded without the game later on.
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.to_string()).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for NetvarDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", DUMP_HEADER)?;
+
+        for (table, netvar) in self.iter() {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t0x{:X}\t{}\t{}\t{}\t{}\t{}",
+                table,
+                netvar.name,
+                netvar.prop_type.name(),
+                netvar.offset,
+                netvar.size,
+                netvar.elements_count,
+                netvar.element_stride,
+                netvar.flags,
+                netvar.table
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_hex(text: &str) -> Option<usize> {
+    usize::from_str_radix(text.strip_prefix("0x")?, 16).ok()
+}
+
+/// Every networked class of the game.
+#[derive(Clone, Debug, Default)]
+pub struct NetvarTree {
+    pub classes: Vec<ClassDump>,
+}
+
+/// A `ClientClass` and its `RecvTable`.
+#[derive(Clone, Debug)]
+pub struct ClassDump {
+    /// The network name, e.g. `CCSPlayer`.
+    pub name: String,
+    pub class_id: i32,
+    pub table: TableDump,
+}
+
+#[derive(Clone, Debug)]
+pub struct TableDump {
+    pub name: String,
+    pub props: Vec<PropDump>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PropDump {
+    pub name: String,
+    pub prop_type: EPropType,
+    pub flags: i32,
+    /// Offset from the start of the entity, not from the table.
+    pub offset: usize,
+    pub elements_count: i32,
+    pub element_stride: i32,
+    /// The nested table of `DataTable` props.
+    pub table: Option<TableDump>,
+}
+
+impl NetvarTree {
+    /// Walks all client classes `client` exposes.
+    pub fn capture(client: &IClient) -> Result<Self, Error> {
+        let mut client_class_ptr = client.get_all_classes();
+
+        if client_class_ptr.is_null() {
+            return Err(Error::Null {
+                item: obfstr!("ClientClass").into(),
+            });
+        }
+
+        let mut classes = Vec::new();
+
+        while !client_class_ptr.is_null() {
+            unsafe {
+                let client_class = client_class_ptr.read();
+
+                classes.push(ClassDump {
+                    name: c_str(client_class.network_name),
+                    class_id: client_class.class_id,
+                    table: TableDump::capture(client_class.recv_table, 0),
+                });
+
+                client_class_ptr = client_class.next as *const ClientClass;
+            }
+        }
+
+        Ok(Self { classes })
+    }
+
+    /// Serializes the tree as pretty printed JSON.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Serializes the tree as TOML, using arrays of tables for classes and props.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        self.write_toml(&mut out)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Writes a C/C++ header with one `#define` per prop and class.
+    ///
+    /// The offsets are flattened into the class, e.g. `CCSPlayer_m_iHealth`.
+    pub fn to_header(&self) -> String {
+        let mut out = String::new();
+        self.write_header(&mut out)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    pub fn write_json<W: Write>(&self, w: &mut W) -> fmt::Result {
+        writeln!(w, "{{")?;
+        writeln!(w, "  \"classes\": [")?;
+
+        for (i, class) in self.classes.iter().enumerate() {
+            writeln!(w, "    {{")?;
+            writeln!(w, "      \"name\": {},", JsonStr(&class.name))?;
+            writeln!(w, "      \"class_id\": {},", class.class_id)?;
+            write!(w, "      \"table\": ")?;
+            class.table.write_json(w, 3)?;
+            writeln!(w)?;
+            writeln!(w, "    }}{}", separator(i, self.classes.len()))?;
+        }
+
+        writeln!(w, "  ]")?;
+        writeln!(w, "}}")
+    }
+
+    pub fn write_toml<W: Write>(&self, w: &mut W) -> fmt::Result {
+        for class in &self.classes {
+            writeln!(w, "[[classes]]")?;
+            writeln!(w, "name = {}", JsonStr(&class.name))?;
+            writeln!(w, "class_id = {}", class.class_id)?;
+            writeln!(w)?;
+            class.table.write_toml(w, "classes.table")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_header<W: Write>(&self, w: &mut W) -> fmt::Result {
+        writeln!(w, "#pragma once")?;
+
+        for class in &self.classes {
+            writeln!(w)?;
+            writeln!(
+                w,
+                "/* {}, class id {}, table {} */",
+                class.name, class.class_id, class.table.name
+            )?;
+
+            let mut written = BTreeSet::new();
+            class
+                .table
+                .write_header(w, &class.name, &class.table.name, &mut written)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TableDump {
+    unsafe fn capture(recv_table: *mut CRecvTable, base_offset: usize) -> Self {
+        let mut props = Vec::new();
+
+        for i in 0..(*recv_table).n_props as isize {
+            let prop = (*recv_table).p_props.offset(i).read();
+            let offset = base_offset + prop.offset as usize;
+
+            props.push(PropDump {
+                name: c_str(prop.prop_name),
+                prop_type: prop.prop_type,
+                flags: prop.prop_flags,
+                offset,
+                elements_count: prop.elements_count,
+                element_stride: prop.element_stride,
+                table: if prop.prop_type == EPropType::DataTable && !prop.data_table.is_null() {
+                    Some(Self::capture(prop.data_table, offset))
+                } else {
+                    None
+                },
+            });
+        }
+
+        Self {
+            name: c_str((*recv_table).table_name),
+            props,
+        }
+    }
+
+    fn write_json<W: Write>(&self, w: &mut W, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+
+        writeln!(w, "{{")?;
+        writeln!(w, "{}  \"name\": {},", indent, JsonStr(&self.name))?;
+        writeln!(w, "{}  \"props\": [", indent)?;
+
+        for (i, prop) in self.props.iter().enumerate() {
+            writeln!(w, "{}    {{", indent)?;
+            writeln!(w, "{}      \"name\": {},", indent, JsonStr(&prop.name))?;
+            writeln!(
+                w,
+                "{}      \"type\": \"{}\",",
+                indent,
+                prop.prop_type.name()
+            )?;
+            writeln!(w, "{}      \"flags\": {},", indent, prop.flags)?;
+            writeln!(w, "{}      \"offset\": {},", indent, prop.offset)?;
+            writeln!(w, "{}      \"elements\": {},", indent, prop.elements_count)?;
+            write!(w, "{}      \"stride\": {}", indent, prop.element_stride)?;
+
+            if let Some(table) = &prop.table {
+                writeln!(w, ",")?;
+                write!(w, "{}      \"table\": ", indent)?;
+                table.write_json(w, depth + 3)?;
+            }
+
+            writeln!(w)?;
+            writeln!(w, "{}    }}{}", indent, separator(i, self.props.len()))?;
+        }
+
+        writeln!(w, "{}  ]", indent)?;
+        write!(w, "{}}}", indent)
+    }
+
+    fn write_toml<W: Write>(&self, w: &mut W, path: &str) -> fmt::Result {
+        writeln!(w, "[{}]", path)?;
+        writeln!(w, "name = {}", JsonStr(&self.name))?;
+        writeln!(w)?;
+
+        for prop in &self.props {
+            writeln!(w, "[[{}.props]]", path)?;
+            writeln!(w, "name = {}", JsonStr(&prop.name))?;
+            writeln!(w, "type = \"{}\"", prop.prop_type.name())?;
+            writeln!(w, "flags = {}", prop.flags)?;
+            writeln!(w, "offset = {}", prop.offset)?;
+            writeln!(w, "elements = {}", prop.elements_count)?;
+            writeln!(w, "stride = {}", prop.element_stride)?;
+            writeln!(w)?;
+
+            if let Some(table) = &prop.table {
+                table.write_toml(w, &format!("{}.props.table", path))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_header<W: Write>(
+        &self,
+        w: &mut W,
+        class: &str,
+        parent: &str,
+        written: &mut BTreeSet<String>,
+    ) -> fmt::Result {
+        for prop in &self.props {
+            // Array elements are named "000", "001", ... after their array.
+            let name = if prop.name.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("{}_{}", parent, prop.name)
+            } else {
+                prop.name.to_string()
+            };
+
+            let define = format!("{}_{}", class, identifier(&name));
+
+            if written.insert(define.clone()) {
+                writeln!(
+                    w,
+                    "#define {} 0x{:X} /* {} */",
+                    define,
+                    prop.offset,
+                    prop.prop_type.name()
+                )?;
+            }
+
+            if let Some(table) = &prop.table {
+                table.write_header(w, class, &name, written)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+unsafe fn c_str(ptr: *const libc::c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    CStr::from_ptr(ptr).to_str().unwrap_or("?").to_string()
+}
+
+fn separator(index: usize, len: usize) -> &'static str {
+    if index + 1 < len {
+        ","
+    } else {
+        ""
+    }
+}
+
+/// Replaces everything that is not allowed in a C identifier with `_`.
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Displays a string as a quoted and escaped JSON (and TOML) string.
+struct JsonStr<'a>(&'a str);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04X}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+
+        f.write_char('"')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = "# netvar dump v2
+DT_CSPlayer\tm_iHealth\tInt\t0x100\t4\t1\t0\t0\tDT_BasePlayer
+DT_CSPlayer\tm_vecOrigin\tVec\t0x138\t12\t1\t0\t4\tDT_BaseEntity
+DT_CSPlayer\tm_iAmmo\tArray\t0x2D8\t0\t32\t4\t0\tDT_LocalPlayerExclusive
+";
+
+    #[test]
+    fn dump_round_trip() {
+        let dump = NetvarDump::parse(DUMP).unwrap();
+        let ammo = dump.get("DT_CSPlayer", "m_iAmmo").unwrap();
+
+        assert_eq!(dump.netvars.len(), 3);
+        assert_eq!(ammo.table, "DT_LocalPlayerExclusive");
+        assert_eq!(ammo.prop_type, EPropType::Array);
+        assert_eq!(ammo.offset, 0x2D8);
+        assert_eq!((ammo.elements_count, ammo.element_stride), (32, 4));
+        assert_eq!(dump.get("DT_CSPlayer", "m_vecOrigin").unwrap().flags, 4);
+        assert_eq!(dump.get("DT_CSPlayer", "m_vecOrigin").unwrap().size, 12);
+
+        let lines = dump.to_string();
+        let mut expected = DUMP.lines().collect::<Vec<_>>();
+        expected[1..].sort_unstable();
+
+        assert_eq!(lines.lines().collect::<Vec<_>>(), expected);
+        assert_eq!(NetvarDump::parse(&lines).unwrap().to_string(), lines);
+    }
+
+    #[test]
+    fn invalid_dump_lines() {
+        for line in &[
+            "DT_CSPlayer\tm_iHealth\tInt\t0x100\t1\t0\t0\tDT_BasePlayer",
+            "DT_CSPlayer\tm_iHealth\tInteger\t0x100\t4\t1\t0\t0\tDT_BasePlayer",
+            "DT_CSPlayer\tm_iHealth\tInt\t100\t4\t1\t0\t0\tDT_BasePlayer",
+            "DT_CSPlayer\tm_iHealth\tInt\t0x100\tfour\t1\t0\t0\tDT_BasePlayer",
+            "DT_CSPlayer\tm_iHealth\tInt\t0x100\t4\tone\t0\t0\tDT_BasePlayer",
+        ] {
+            let text = format!("{}{}\n", DUMP, line);
+
+            match NetvarDump::parse(&text) {
+                Err(Error::Other { msg }) => assert!(msg.starts_with("invalid netvar dump line 5")),
+                result => panic!("{:?} parsed as {:?}", line, result),
+            }
+        }
+    }
+
+    #[test]
+    fn dump_header() {
+        let body = DUMP.split_once('\n').unwrap().1;
+
+        for (text, error) in &[
+            (
+                body.to_string(),
+                "netvar dump doesn't start with \"# netvar dump v2\"",
+            ),
+            (
+                String::new(),
+                "netvar dump doesn't start with \"# netvar dump v2\"",
+            ),
+            (
+                format!("# netvar dump v1\n{}", body),
+                "unsupported netvar dump version: # netvar dump v1",
+            ),
+        ] {
+            match NetvarDump::parse(text) {
+                Err(Error::Other { msg }) => assert_eq!(msg, *error),
+                result => panic!("{:?} parsed as {:?}", text, result),
+            }
+        }
+
+        assert!(NetvarDump::parse(&DUMP.replace('\n', "\r\n")).is_ok());
+    }
+
+    fn prop(name: &str, prop_type: EPropType, offset: usize) -> PropDump {
+        PropDump {
+            name: name.into(),
+            prop_type,
+            flags: 0,
+            offset,
+            elements_count: 1,
+            element_stride: 0,
+            table: None,
+        }
+    }
+
+    fn tree() -> NetvarTree {
+        let mut local = prop("m_Local", EPropType::DataTable, 0x2FC);
+        local.table = Some(TableDump {
+            name: "DT_Local".into(),
+            props: vec![prop("m_aimPunchAngle", EPropType::Vec, 0x302C)],
+        });
+
+        let mut ammo = prop("m_iAmmo", EPropType::DataTable, 0x2D8);
+        ammo.table = Some(TableDump {
+            name: "m_iAmmo".into(),
+            props: vec![
+                prop("000", EPropType::Int, 0x2D8),
+                prop("001", EPropType::Int, 0x2DC),
+            ],
+        });
+
+        NetvarTree {
+            classes: vec![ClassDump {
+                name: "CCSPlayer".into(),
+                class_id: 40,
+                table: TableDump {
+                    name: "DT_CSPlayer".into(),
+                    props: vec![prop("m_iHealth", EPropType::Int, 0x100), local, ammo],
+                },
+            }],
+        }
+    }
+
+    #[test]
+    fn json() {
+        let json = tree().to_json();
+
+        assert!(json.starts_with("{\n  \"classes\": [\n    {\n      \"name\": \"CCSPlayer\",\n"));
+        assert!(json.contains(
+            "            \"name\": \"m_iHealth\",\n            \"type\": \"Int\",\n            \"flags\": 0,\n            \"offset\": 256,\n            \"elements\": 1,\n            \"stride\": 0\n          },\n"
+        ));
+        assert!(json.contains("\"stride\": 0,\n            \"table\": {\n"));
+        assert!(json.ends_with("    }\n  ]\n}\n"));
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+        assert_eq!(json.matches('[').count(), json.matches(']').count());
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(JsonStr("a\"b\\c\n").to_string(), "\"a\\\"b\\\\c\\u000A\"");
+    }
+
+    #[test]
+    fn toml() {
+        let toml = tree().to_toml();
+
+        assert!(toml.starts_with(
+            "[[classes]]\nname = \"CCSPlayer\"\nclass_id = 40\n\n[classes.table]\nname = \"DT_CSPlayer\"\n\n"
+        ));
+        assert!(toml.contains(
+            "[[classes.table.props]]\nname = \"m_iHealth\"\ntype = \"Int\"\nflags = 0\noffset = 256\nelements = 1\nstride = 0\n"
+        ));
+        assert!(toml.contains(
+            "[classes.table.props.table]\nname = \"DT_Local\"\n\n[[classes.table.props.table.props]]\nname = \"m_aimPunchAngle\"\n"
+        ));
+    }
+
+    #[test]
+    fn header() {
+        assert_eq!(
+            tree().to_header(),
+            "#pragma once\n\
+             \n\
+             /* CCSPlayer, class id 40, table DT_CSPlayer */\n\
+             #define CCSPlayer_m_iHealth 0x100 /* Int */\n\
+             #define CCSPlayer_m_Local 0x2FC /* DataTable */\n\
+             #define CCSPlayer_m_aimPunchAngle 0x302C /* Vec */\n\
+             #define CCSPlayer_m_iAmmo 0x2D8 /* DataTable */\n\
+             #define CCSPlayer_m_iAmmo_000 0x2D8 /* Int */\n\
+             #define CCSPlayer_m_iAmmo_001 0x2DC /* Int */\n"
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Every NetVar the SDK declares through `netvar!`.
+//!
+//! `netvar!` resolves its table and name lazily and falls back to a default value,
+//! so a renamed prop would otherwise go unnoticed. `sdk::initialize` checks every
+//! declaration against the scanned tables and lists the broken ones in its report.
+//! # Examples
+//! ```no_run
+//! use sdk::netvar::registry;
+//!
+//! for (declaration, result) in registry::validate() {
+//!     if let Err(error) = result {
+//!         println!("{}: {}", declaration, error);
+//!     }
+//! }
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use static_init::dynamic;
+
+use crate::definitions::recv_props::EPropType;
+use crate::netvar::dump::NetvarDump;
+use crate::netvar::value::check_size;
+use crate::netvar::{get_netvar, Netvar, NetvarArray};
+use crate::utils::error::Error;
+use crate::utils::math::vector::{Vec2, Vec3};
+
+/// Rust types a NetVar can be read as, and the prop types they are compatible with.
+pub trait NetvarType {
+    const PROP_TYPES: &'static [EPropType];
+
+    /// Reads a value from the possibly unaligned `address`.
+    /// # Safety
+    /// `address` has to point to `size_of::<Self>()` readable bytes.
+    unsafe fn read_from(address: usize) -> Self
+    where
+        Self: Sized + Copy,
+    {
+        (address as *const Self).read_unaligned()
+    }
+}
+
+macro_rules! netvar_type {
+    ($prop_types:expr, $($ty:ty),*) => {
+        $(impl NetvarType for $ty {
+            const PROP_TYPES: &'static [EPropType] = $prop_types;
+        })*
+    };
+}
+
+netvar_type!(&[EPropType::Int], i8, u8, i16, u16, i32, u32, usize, isize);
+netvar_type!(&[EPropType::Int64], i64, u64);
+netvar_type!(&[EPropType::Float], f32);
+netvar_type!(&[EPropType::Vec], Vec3);
+// QAngles are networked as vectors, but often only pitch and yaw are read.
+netvar_type!(&[EPropType::VecXY, EPropType::Vec], Vec2);
+
+// Any byte but `0` is `true`, reading it as a `bool` directly would be undefined.
+impl NetvarType for bool {
+    const PROP_TYPES: &'static [EPropType] = &[EPropType::Int];
+
+    unsafe fn read_from(address: usize) -> Self {
+        (address as *const u8).read() != 0
+    }
+}
+
+// `RecvPropArray` props are `Array`s, `RecvPropArray3` props are tables of their elements.
+impl<T> NetvarType for NetvarArray<T> {
+    const PROP_TYPES: &'static [EPropType] = &[EPropType::Array, EPropType::DataTable];
+}
+
+/// A single `netvar!` accessor.
+#[derive(Clone, Debug)]
+pub struct NetvarDecl {
+    /// Full path of the type the accessor is declared on.
+    pub owner: &'static str,
+    pub accessor: &'static str,
+    pub table: String,
+    pub name: String,
+    /// Offset the accessor adds to the NetVar's offset.
+    ///
+    /// Accessors with an extra offset read a different field than the prop,
+    /// so only the existence of the prop is checked for them.
+    pub extra: usize,
+    pub prop_types: &'static [EPropType],
+    /// Size of the value the accessor reads, `0` for arrays, whose elements are checked
+    /// when they are accessed.
+    pub size: usize,
+}
+
+/// Functions returning the declarations of each type that uses `netvars!`, filled
+/// before `main` by the constructors the macro emits.
+#[dynamic]
+static mut DECLARATIONS: Vec<fn() -> Vec<NetvarDecl>> = Vec::new();
+
+/// Adds the declarations of a type to [`declared`], called by `netvars!`.
+#[doc(hidden)]
+pub fn register(declarations: fn() -> Vec<NetvarDecl>) {
+    DECLARATIONS.write().push(declarations);
+}
+
+impl NetvarDecl {
+    /// Looks the declaration up in the scanned NetVars.
+    pub fn check(&self) -> Result<Netvar, Error> {
+        self.check_netvar(get_netvar(&self.table, &self.name))
+    }
+
+    /// Looks the declaration up in `dump` instead of the scanned NetVars.
+    pub fn check_dump(&self, dump: &NetvarDump) -> Result<Netvar, Error> {
+        self.check_netvar(dump.get(&self.table, &self.name).cloned())
+    }
+
+    fn check_netvar(&self, netvar: Option<Netvar>) -> Result<Netvar, Error> {
+        let netvar = netvar.ok_or_else(|| Error::NotFound {
+            item: format!("{}->{}", self.table, self.name),
+        })?;
+
+        if self.extra == 0 {
+            check_type(&self.table, &netvar, self.prop_types)?;
+
+            if self.size != 0 {
+                check_size(&self.table, &netvar, self.size)?;
+            }
+        }
+
+        Ok(netvar)
+    }
+}
+
+/// Fails if `netvar` of `table` is none of `prop_types`.
+pub(crate) fn check_type(
+    table: &str,
+    netvar: &Netvar,
+    prop_types: &[EPropType],
+) -> Result<(), Error> {
+    if prop_types.contains(&netvar.prop_type) {
+        return Ok(());
+    }
+
+    Err(Error::Other {
+        msg: format!(
+            "{}->{} is {}, expected {}",
+            table,
+            netvar.name,
+            netvar.prop_type.name(),
+            prop_types
+                .iter()
+                .map(EPropType::name)
+                .collect::<Vec<_>>()
+                .join(" or ")
+        ),
+    })
+}
+
+impl fmt::Display for NetvarDecl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let owner = self.owner.rsplit("::").next().unwrap_or(self.owner);
+
+        write!(f, "{}::{}", owner, self.accessor)
+    }
+}
+
+/// Returns every NetVar declared through `netvar!` in this crate.
+pub fn declared() -> Vec<NetvarDecl> {
+    DECLARATIONS
+        .read()
+        .iter()
+        .flat_map(|declarations| declarations())
+        .collect()
+}
+
+/// Checks every declared NetVar against the scanned tables.
+pub fn validate() -> Vec<(NetvarDecl, Result<Netvar, Error>)> {
+    declared()
+        .into_iter()
+        .map(|declaration| {
+            let result = declaration.check();
+            (declaration, result)
+        })
+        .collect()
+}
+
+/// Checks every declared NetVar against `dump`, e.g. a scan that isn't installed yet.
+pub fn validate_dump(dump: &NetvarDump) -> Vec<(NetvarDecl, Result<Netvar, Error>)> {
+    declared()
+        .into_iter()
+        .map(|declaration| {
+            let result = declaration.check_dump(dump);
+            (declaration, result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn declaration(name: &str, prop_types: &'static [EPropType], size: usize) -> NetvarDecl {
+        NetvarDecl {
+            owner: "sdk::classes::entity::CEntity",
+            accessor: "get_value",
+            table: "DT_BasePlayer".into(),
+            name: name.into(),
+            extra: 0,
+            prop_types,
+            size,
+        }
+    }
+
+    fn netvar(name: &str, prop_type: EPropType, size: usize) -> Netvar {
+        Netvar {
+            table: "DT_BasePlayer".into(),
+            name: name.into(),
+            prop_type,
+            flags: 0,
+            offset: 0x100,
+            size,
+            elements_count: 1,
+            element_stride: 0,
+        }
+    }
+
+    #[test]
+    fn registered_by_the_macro() {
+        let owners = declared()
+            .iter()
+            .map(|declaration| declaration.to_string())
+            .collect::<Vec<_>>();
+
+        for accessor in &[
+            "CEntity::get_health",
+            "CWeapon::get_fallback_seed",
+            "CBaseViewModel::get_weapon",
+        ] {
+            assert!(owners.iter().any(|owner| owner == accessor), "{}", accessor);
+        }
+    }
+
+    #[test]
+    fn checks_size() {
+        let health = netvar("m_iHealth", EPropType::Int, 4);
+
+        assert!(declaration("m_iHealth", i32::PROP_TYPES, 4)
+            .check_netvar(Some(health.clone()))
+            .is_ok());
+        assert!(declaration("m_iHealth", u8::PROP_TYPES, 1)
+            .check_netvar(Some(health))
+            .is_err());
+        assert!(declaration("m_lifeState", bool::PROP_TYPES, 1)
+            .check_netvar(Some(netvar("m_lifeState", EPropType::Int, 1)))
+            .is_ok());
+    }
+
+    #[test]
+    fn arrays_skip_the_size_check() {
+        let view_models = netvar("m_hViewModel", EPropType::Array, 0);
+
+        assert!(
+            declaration("m_hViewModel", NetvarArray::<i32>::PROP_TYPES, 0)
+                .check_netvar(Some(view_models))
+                .is_ok()
+        );
+    }
+}
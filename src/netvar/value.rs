@@ -0,0 +1,216 @@
+//! Reading and writing NetVars by name at runtime.
+//!
+//! Unlike `netvar!` accessors, the table and name are only known at runtime, so every
+//! access is checked against the prop type found by the scan instead of trusting the caller.
+//! These are used by the `get_prop` and `set_prop` methods of [`crate::classes::Entity`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::definitions::recv_props::EPropType;
+use crate::netvar::registry::{check_type, NetvarType};
+use crate::netvar::{get_netvar, Netvar};
+use crate::utils::error::Error;
+use crate::utils::math::vector::{Vec2, Vec3};
+
+/// The longest string a `RecvProp` can hold, including the terminator.
+const MAX_STRING_LENGTH: usize = 512;
+
+/// `SPROP_UNSIGNED`, set on props that hold unsigned integers.
+const UNSIGNED: i32 = 1 << 0;
+
+/// A NetVar value of any prop type that holds a single value.
+#[derive(Clone)]
+pub enum PropValue {
+    Int(i32),
+    Float(f32),
+    Vec(Vec3),
+    VecXY(Vec2),
+    String(String),
+    Int64(i64),
+}
+
+impl PropValue {
+    pub fn prop_type(&self) -> EPropType {
+        match self {
+            PropValue::Int(_) => EPropType::Int,
+            PropValue::Float(_) => EPropType::Float,
+            PropValue::Vec(_) => EPropType::Vec,
+            PropValue::VecXY(_) => EPropType::VecXY,
+            PropValue::String(_) => EPropType::String,
+            PropValue::Int64(_) => EPropType::Int64,
+        }
+    }
+}
+
+fn lookup(base: *const usize, table: &str, name: &str) -> Result<Netvar, Error> {
+    if base.is_null() {
+        return Err(Error::Null {
+            item: obfstr!("entity").into(),
+        });
+    }
+
+    get_netvar(table, name).ok_or_else(|| Error::NotFound {
+        item: format!("{}->{}", table, name),
+    })
+}
+
+/// Returns how many bytes `netvar` takes up in the entity, `0` if unknown.
+///
+/// `Int` props with a custom proxy are assumed to hold a whole `int`, which is what the
+/// proxy receives.
+fn value_size(netvar: &Netvar) -> usize {
+    match (netvar.prop_type, netvar.size) {
+        (EPropType::Int, 0) => size_of::<i32>(),
+        (_, size) => size,
+    }
+}
+
+/// Fails if a value of `size` bytes doesn't fit `netvar` of `table` exactly.
+pub(crate) fn check_size(table: &str, netvar: &Netvar, size: usize) -> Result<(), Error> {
+    let expected = value_size(netvar);
+
+    // A `Vec2` is the first two components of a `Vec`.
+    if expected == 0
+        || expected == size
+        || netvar.prop_type == EPropType::Vec && size == size_of::<Vec2>()
+    {
+        return Ok(());
+    }
+
+    Err(Error::Other {
+        msg: format!(
+            "{}->{} is {} bytes, expected {}",
+            table, netvar.name, expected, size
+        ),
+    })
+}
+
+/// Reads the NetVar `table->name` of the entity at `base` as `T`.
+///
+/// Fails if the NetVar does not exist or its prop type or size doesn't fit `T`.
+/// # Safety
+/// `base` has to be null or point to an entity that has the NetVar.
+pub unsafe fn read<T: NetvarType + Copy>(
+    base: *const usize,
+    table: &str,
+    name: &str,
+) -> Result<T, Error> {
+    let netvar = lookup(base, table, name)?;
+    check_type(table, &netvar, T::PROP_TYPES)?;
+    check_size(table, &netvar, size_of::<T>())?;
+
+    Ok(T::read_from(base as usize + netvar.offset))
+}
+
+/// Writes `value` to the NetVar `table->name` of the entity at `base`.
+/// # Safety
+/// See [`read`].
+pub unsafe fn write<T: NetvarType + Copy>(
+    base: *const usize,
+    table: &str,
+    name: &str,
+    value: T,
+) -> Result<(), Error> {
+    let netvar = lookup(base, table, name)?;
+    check_type(table, &netvar, T::PROP_TYPES)?;
+    check_size(table, &netvar, size_of::<T>())?;
+
+    ((base as usize + netvar.offset) as *mut T).write_unaligned(value);
+
+    Ok(())
+}
+
+/// Reads the NetVar `table->name` of the entity at `base` as whatever its prop type is.
+///
+/// `Int` props narrower than an `int` are extended to one, by their sign unless they are
+/// unsigned. Fails for `Array` and `DataTable` props, which don't hold a single value.
+/// # Safety
+/// See [`read`].
+pub unsafe fn read_value(base: *const usize, table: &str, name: &str) -> Result<PropValue, Error> {
+    let netvar = lookup(base, table, name)?;
+    let address = base as usize + netvar.offset;
+    let unsigned = netvar.flags & UNSIGNED != 0;
+
+    Ok(match netvar.prop_type {
+        EPropType::Int => PropValue::Int(match value_size(&netvar) {
+            1 if unsigned => (address as *const u8).read() as i32,
+            1 => (address as *const i8).read() as i32,
+            2 if unsigned => (address as *const u16).read_unaligned() as i32,
+            2 => (address as *const i16).read_unaligned() as i32,
+            _ => (address as *const i32).read_unaligned(),
+        }),
+        EPropType::Float => PropValue::Float((address as *const f32).read_unaligned()),
+        EPropType::Vec => PropValue::Vec((address as *const Vec3).read_unaligned()),
+        EPropType::VecXY => PropValue::VecXY((address as *const Vec2).read_unaligned()),
+        EPropType::Int64 => PropValue::Int64((address as *const i64).read_unaligned()),
+        EPropType::String => {
+            let length = match netvar.size {
+                0 => MAX_STRING_LENGTH,
+                size => size,
+            };
+            let bytes = (0..length)
+                .map(|index| ((address + index) as *const u8).read())
+                .take_while(|byte| *byte != 0)
+                .collect::<Vec<_>>();
+
+            PropValue::String(String::from_utf8_lossy(&bytes).into())
+        }
+        EPropType::Array | EPropType::DataTable => {
+            return Err(Error::Other {
+                msg: format!(
+                    "{}->{} is {}, which has no single value",
+                    table,
+                    name,
+                    netvar.prop_type.name()
+                ),
+            })
+        }
+    })
+}
+
+/// Writes `value` to the NetVar `table->name`, which has to have the same prop type.
+///
+/// `Int` values are truncated to the size of the prop. Strings have to fit their buffer,
+/// including the terminator.
+/// # Safety
+/// See [`read`].
+pub unsafe fn write_value(
+    base: *const usize,
+    table: &str,
+    name: &str,
+    value: &PropValue,
+) -> Result<(), Error> {
+    let netvar = lookup(base, table, name)?;
+    check_type(table, &netvar, &[value.prop_type()])?;
+
+    let address = base as usize + netvar.offset;
+
+    match value {
+        PropValue::Int(value) => match value_size(&netvar) {
+            1 => (address as *mut u8).write(*value as u8),
+            2 => (address as *mut u16).write_unaligned(*value as u16),
+            _ => (address as *mut i32).write_unaligned(*value),
+        },
+        PropValue::Float(value) => (address as *mut f32).write_unaligned(*value),
+        PropValue::Vec(value) => (address as *mut Vec3).write_unaligned(*value),
+        PropValue::VecXY(value) => (address as *mut Vec2).write_unaligned(*value),
+        PropValue::Int64(value) => (address as *mut i64).write_unaligned(*value),
+        PropValue::String(value) => {
+            if value.len() >= netvar.size {
+                return Err(Error::Other {
+                    msg: format!(
+                        "\"{}\" doesn't fit {}->{}, which holds {} bytes",
+                        value, table, name, netvar.size
+                    ),
+                });
+            }
+
+            let bytes = [value.as_bytes(), &[0]].concat();
+            core::ptr::copy_nonoverlapping(bytes.as_ptr(), address as *mut u8, bytes.len());
+        }
+    }
+
+    Ok(())
+}
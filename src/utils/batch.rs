@@ -0,0 +1,340 @@
+//! Scanning for many signatures in a single pass.
+//!
+//! Every signature contributes its longest run of concrete bytes to an Aho-Corasick
+//! automaton. The automaton walks each section once, and the full signature is only
+//! compared where one of those runs was found, so adding a signature barely adds to the
+//! time a scan takes.
+//! # Examples
+//! ```no_run
+//! use sdk::signature;
+//! use sdk::utils::batch::SignatureSet;
+//! use sdk::utils::memory::Sections;
+//! use sdk::utils::platform::modules;
+//!
+//! let results = SignatureSet::new()
+//!     .with("IGlowMgr", signature!("0F 11 05 ? ? ? ? 83 C8 01"))
+//!     .with("IInput", signature!("B9 ? ? ? ? 8B 40 38 FF D0 84 C0 0F 85"))
+//!     .scan_module(modules::CLIENT, Sections::Executable);
+//!
+//! for name in &results.misses {
+//!     println!("{} was not found", name);
+//! }
+//! ```
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use libc::c_char;
+
+use crate::utils::memory::{module_sections, Sections};
+use crate::utils::signature::Signature;
+
+/// Longest run of concrete bytes taken from a signature, longer runs only make the automaton bigger.
+const MAX_LITERAL_LENGTH: usize = 16;
+const NONE: u32 = u32::MAX;
+
+/// Named signatures that are scanned for together.
+#[derive(Clone, Default)]
+pub struct SignatureSet {
+    entries: Vec<(String, Signature)>,
+}
+
+/// Where the signatures of a [`SignatureSet`] were found.
+#[derive(Clone, Debug, Default)]
+pub struct ScanResults {
+    /// The first match of every signature that was found, by name.
+    pub found: BTreeMap<String, usize>,
+    /// The names of the signatures that were not found, in the order they were added.
+    pub misses: Vec<String>,
+}
+
+impl ScanResults {
+    /// Returns the first match of the signature called `name`.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.found.get(name).copied()
+    }
+}
+
+impl SignatureSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `signature` under `name`, replacing a signature with the same name.
+    pub fn add(&mut self, name: impl Into<String>, signature: Signature) -> &mut Self {
+        let name = name.into();
+
+        self.entries.retain(|(existing, _)| *existing != name);
+        self.entries.push((name, signature));
+
+        self
+    }
+
+    /// Same as [`SignatureSet::add`], but takes and returns the set by value.
+    pub fn with(mut self, name: impl Into<String>, signature: Signature) -> Self {
+        self.add(name, signature);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Scans `haystack`, the results are offsets into it.
+    /// # Examples
+    /// ```
+    /// use sdk::signature;
+    /// use sdk::utils::batch::SignatureSet;
+    ///
+    /// let results = SignatureSet::new()
+    ///     .with("call", signature!("E8 ? ? ? ? 84 C0"))
+    ///     .with("mov", signature!("B9 ? ? ? ? 8B 40 38"))
+    ///     .with("missing", signature!("CC CC CC"))
+    ///     .scan(b"\x90\xB9\x01\x02\x03\x04\x8B\x40\x38\xE8\x00\x00\x00\x00\x84\xC0");
+    ///
+    /// assert_eq!(results.get("mov"), Some(1));
+    /// assert_eq!(results.get("call"), Some(9));
+    /// assert_eq!(results.misses, vec!["missing".to_string()]);
+    /// ```
+    pub fn scan(&self, haystack: &[u8]) -> ScanResults {
+        let mut results = ScanResults::default();
+
+        Scanner::new(self).scan(haystack, 0, &mut results);
+
+        self.finish(results)
+    }
+
+    /// Scans the given sections of a loaded module, the results are addresses.
+    pub fn scan_module(&self, module_name: *const c_char, sections: Sections) -> ScanResults {
+        let mut results = ScanResults::default();
+        let scanner = Scanner::new(self);
+
+        let mut sections_to_scan = module_sections(module_name);
+        sections_to_scan.sort_by_key(|section| section.address);
+
+        for section in sections_to_scan
+            .iter()
+            .filter(|section| sections.includes(section))
+        {
+            if scanner.scan(unsafe { section.bytes() }, section.address, &mut results) {
+                break;
+            }
+        }
+
+        self.finish(results)
+    }
+
+    fn finish(&self, mut results: ScanResults) -> ScanResults {
+        results.misses = self
+            .entries
+            .iter()
+            .filter(|(name, _)| !results.found.contains_key(name))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        results
+    }
+}
+
+/// Returns the offset and bytes of the longest run of concrete bytes in `signature`.
+fn literal(signature: &Signature) -> (usize, &[u8]) {
+    let mask = signature.mask();
+    let (mut best_start, mut best_length) = (0, 0);
+    let mut start = 0;
+
+    for i in 0..=mask.len() {
+        if i < mask.len() && mask[i] {
+            continue;
+        }
+
+        if i - start > best_length {
+            best_start = start;
+            best_length = i - start;
+        }
+
+        start = i + 1;
+    }
+
+    let length = best_length.min(MAX_LITERAL_LENGTH);
+
+    (
+        best_start,
+        &signature.bytes()[best_start..best_start + length],
+    )
+}
+
+/// An Aho-Corasick automaton over the literals of a [`SignatureSet`].
+struct Scanner<'a> {
+    set: &'a SignatureSet,
+    /// The literal offset of every signature.
+    offsets: Vec<usize>,
+    lengths: Vec<usize>,
+    /// The next state for every state and byte.
+    transitions: Vec<[u32; 256]>,
+    /// The signatures whose literal ends in a state.
+    outputs: Vec<Vec<usize>>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(set: &'a SignatureSet) -> Self {
+        let mut scanner = Self {
+            set,
+            offsets: Vec::new(),
+            lengths: Vec::new(),
+            transitions: vec![[NONE; 256]],
+            outputs: vec![Vec::new()],
+        };
+
+        for (index, (_, signature)) in set.entries.iter().enumerate() {
+            let (offset, bytes) = literal(signature);
+            let mut state = 0;
+
+            for byte in bytes {
+                if scanner.transitions[state][*byte as usize] == NONE {
+                    scanner.transitions[state][*byte as usize] = scanner.transitions.len() as u32;
+                    scanner.transitions.push([NONE; 256]);
+                    scanner.outputs.push(Vec::new());
+                }
+
+                state = scanner.transitions[state][*byte as usize] as usize;
+            }
+
+            scanner.offsets.push(offset);
+            scanner.lengths.push(bytes.len());
+            scanner.outputs[state].push(index);
+        }
+
+        scanner.link();
+        scanner
+    }
+
+    /// Turns the trie into a complete automaton by following the failure links.
+    fn link(&mut self) {
+        let mut failures = vec![0u32; self.transitions.len()];
+        let mut queue = VecDeque::new();
+
+        for byte in 0..256 {
+            match self.transitions[0][byte] {
+                NONE => self.transitions[0][byte] = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let failure = failures[state] as usize;
+            let inherited = self.outputs[failure].clone();
+            self.outputs[state].extend(inherited);
+
+            for byte in 0..256 {
+                let fallback = self.transitions[failure][byte];
+
+                match self.transitions[state][byte] {
+                    NONE => self.transitions[state][byte] = fallback,
+                    child => {
+                        failures[child as usize] = fallback;
+                        queue.push_back(child as usize);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the first match of every signature in `haystack` that was not found yet,
+    /// offset by `base`. Returns `true` once every signature was found.
+    fn scan(&self, haystack: &[u8], base: usize, results: &mut ScanResults) -> bool {
+        let mut state = 0;
+
+        for (position, byte) in haystack.iter().enumerate() {
+            state = self.transitions[state][*byte as usize] as usize;
+
+            for index in &self.outputs[state] {
+                let (name, signature) = &self.set.entries[*index];
+
+                if results.found.contains_key(name) {
+                    continue;
+                }
+
+                let start =
+                    match (position + 1).checked_sub(self.lengths[*index] + self.offsets[*index]) {
+                        Some(start) => start,
+                        None => continue,
+                    };
+
+                if signature.matches(&haystack[start..]) {
+                    results.found.insert(name.clone(), base + start);
+
+                    if results.found.len() == self.set.len() {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature;
+
+    fn assert_matches_find(set: &SignatureSet, haystack: &[u8]) {
+        let results = set.scan(haystack);
+
+        for (name, signature) in &set.entries {
+            assert_eq!(results.get(name), signature.find(haystack), "{}", name);
+        }
+    }
+
+    #[test]
+    fn overlapping_literals() {
+        let set = SignatureSet::new()
+            .with("outer", signature!("8B 45 08 8B 4D 0C"))
+            .with("inner", signature!("45 08 8B"))
+            .with("suffix", signature!("? 08 8B 4D"))
+            .with("wildcards", signature!("? ? 8B 45 ? ? 4D"))
+            .with("late", signature!("8B 45 08 8B 4D 10"));
+
+        // The literal of "late" shows up before its only full match.
+        assert_matches_find(
+            &set,
+            b"\x8B\x45\x08\x8B\x4D\x0C\x90\x90\x8B\x45\x08\x8B\x4D\x10",
+        );
+        assert_matches_find(&set, b"\x45\x08\x8B\x4D");
+        assert_matches_find(&set, b"");
+    }
+
+    #[test]
+    fn generated_haystack() {
+        let mut state = 0x2545_F491_u32;
+        let haystack = (0..0x4000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                // A small alphabet so that literals match in many places.
+                (state % 5) as u8 * 0x11
+            })
+            .collect::<Vec<_>>();
+
+        let mut set = SignatureSet::new();
+
+        for (index, start) in (0..haystack.len() - 16).step_by(0x1F3).enumerate() {
+            let length = 4 + index % 9;
+            let bytes = haystack[start..start + length].to_vec();
+            let mask = (0..length).map(|i| i == 0 || i % 3 != 1).collect();
+
+            set.add(format!("{}", index), Signature::new(bytes, mask).unwrap());
+        }
+
+        set.add("missing", Signature::parse("12 34 ? 56").unwrap());
+
+        assert_matches_find(&set, &haystack);
+    }
+}
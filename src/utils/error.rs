@@ -11,12 +11,16 @@
 //! ```
 
 use crate::alloc::string::ToString;
+use crate::interface::LoadReport;
 use alloc::string::String;
 use custom_error::custom_error;
 
-custom_error! {pub Error
+custom_error! {#[derive(Clone)] pub Error
     Null{item:String} = "{item} is null.",
     NotFound{item:String} = "couldn't find {item}.",
+    Resolve{item:String, step:String, reason:String} = "couldn't resolve {item}, step {step} failed: {reason}.",
     Other{msg:String} = "{msg}",
-    Unknown{message:String} = "unknown error: {message}"
+    Unknown{message:String} = "unknown error: {message}",
+    Load{report:LoadReport} = "failed to load {report}.",
+    Uninitialized = "the sdk is not initialized."
 }
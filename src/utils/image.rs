@@ -0,0 +1,345 @@
+//! Parsing module files on disk, PE images on Windows and ELF objects on Linux.
+//!
+//! Only what is needed to look at a module's code without loading it is parsed: its
+//! sections, where they are mapped and, for PE images, the relocations.
+//! # Examples
+//! ```
+//! use sdk::utils::image::{Format, Image};
+//! use sdk::utils::x86::Mode;
+//!
+//! let file = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+//! let image = Image::parse(&file)?;
+//!
+//! #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+//! assert_eq!((image.format, image.mode), (Format::Elf, Mode::X64));
+//! assert!(image.sections.iter().any(|section| section.executable));
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::utils::error::Error;
+use crate::utils::x86::Mode;
+
+const PE_SIGNATURE: &[u8] = b"PE\0\0";
+const ELF_MAGIC: &[u8] = b"\x7FELF";
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+const IMAGE_REL_BASED_HIGHLOW: u16 = 3;
+const IMAGE_REL_BASED_DIR64: u16 = 10;
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    Pe,
+    Elf,
+}
+
+/// A PE section or, like [`crate::utils::memory::module_sections`], an ELF `PT_LOAD` segment.
+#[derive(Clone, Debug)]
+pub struct ImageSection<'a> {
+    pub name: String,
+    /// Where the section is mapped, relative to the image base.
+    pub rva: usize,
+    /// The size of the section once mapped.
+    pub size: usize,
+    pub executable: bool,
+    pub writable: bool,
+    /// The contents of the section in the file, can be shorter than `size`.
+    pub data: &'a [u8],
+}
+
+/// A module file.
+#[derive(Clone, Debug)]
+pub struct Image<'a> {
+    pub format: Format,
+    pub mode: Mode,
+    /// The address the image prefers to be loaded at, `0` for ELF objects.
+    pub image_base: usize,
+    pub sections: Vec<ImageSection<'a>>,
+    /// The RVA and size of every value the loader relocates, always empty for ELF objects.
+    pub relocations: Vec<(usize, usize)>,
+}
+
+impl<'a> Image<'a> {
+    /// Parses a PE image or ELF object, both have to be x86 or x86-64.
+    pub fn parse(file: &'a [u8]) -> Result<Self, Error> {
+        if file.starts_with(b"MZ") {
+            parse_pe(file)
+        } else if file.starts_with(ELF_MAGIC) {
+            parse_elf(file)
+        } else {
+            Err(invalid("the file is neither a PE image nor an ELF object"))
+        }
+    }
+
+    /// Returns the section `rva` is in.
+    pub fn section_at(&self, rva: usize) -> Option<&ImageSection<'a>> {
+        self.sections
+            .iter()
+            .find(|section| rva >= section.rva && rva - section.rva < section.size)
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::Other {
+        msg: format!("invalid module file: {}", reason),
+    }
+}
+
+/// Adds a size to an offset read from the file, failing instead of overflowing.
+fn add(offset: usize, size: usize) -> Result<usize, Error> {
+    offset.checked_add(size).ok_or_else(|| invalid("truncated"))
+}
+
+/// Returns the `size` bytes of `file` at `offset`.
+fn bytes(file: &[u8], offset: usize, size: usize) -> Result<&[u8], Error> {
+    file.get(offset..add(offset, size)?)
+        .ok_or_else(|| invalid("truncated"))
+}
+
+/// Returns the offset of the `index`th entry of the table at `offset`, whose entries are
+/// `size` bytes large.
+fn table_entry(offset: usize, index: usize, size: usize) -> Result<usize, Error> {
+    index
+        .checked_mul(size)
+        .and_then(|start| offset.checked_add(start))
+        .ok_or_else(|| invalid("truncated"))
+}
+
+fn read<const N: usize>(file: &[u8], offset: usize) -> Result<[u8; N], Error> {
+    let mut value = [0; N];
+
+    value.copy_from_slice(bytes(file, offset, N)?);
+
+    Ok(value)
+}
+
+fn u16_at(file: &[u8], offset: usize) -> Result<u16, Error> {
+    read(file, offset).map(u16::from_le_bytes)
+}
+
+fn u32_at(file: &[u8], offset: usize) -> Result<u32, Error> {
+    read(file, offset).map(u32::from_le_bytes)
+}
+
+fn u64_at(file: &[u8], offset: usize) -> Result<u64, Error> {
+    read(file, offset).map(u64::from_le_bytes)
+}
+
+/// Returns the part of `file` from `offset` with at most `size` bytes.
+fn data(file: &[u8], offset: usize, size: usize) -> &[u8] {
+    let start = offset.min(file.len());
+
+    &file[start..start.saturating_add(size).min(file.len())]
+}
+
+fn parse_pe(file: &[u8]) -> Result<Image<'_>, Error> {
+    let nt_headers = u32_at(file, 0x3C)? as usize;
+
+    if bytes(file, nt_headers, 4)? != PE_SIGNATURE {
+        return Err(invalid("missing PE signature"));
+    }
+
+    let file_header = add(nt_headers, 4)?;
+    let mode = match u16_at(file, file_header)? {
+        0x014C => Mode::X86,
+        0x8664 => Mode::X64,
+        _ => return Err(invalid("not an x86 image")),
+    };
+
+    let section_count = u16_at(file, file_header + 2)? as usize;
+    let optional_header = file_header + 20;
+    let optional_header_size = u16_at(file, file_header + 16)? as usize;
+
+    let (image_base, directories) = match u16_at(file, optional_header)? {
+        0x010B => (
+            u32_at(file, optional_header + 28)? as usize,
+            optional_header + 96,
+        ),
+        0x020B => (
+            u64_at(file, optional_header + 24)? as usize,
+            optional_header + 112,
+        ),
+        _ => return Err(invalid("unknown optional header")),
+    };
+
+    let directory_count = u32_at(file, directories - 4)? as usize;
+
+    let section_headers = add(optional_header, optional_header_size)?;
+
+    let sections = (0..section_count)
+        .map(|i| {
+            let header = bytes(file, table_entry(section_headers, i, 40)?, 40)?;
+            let name = read::<8>(header, 0)?;
+            let name_length = name.iter().position(|byte| *byte == 0).unwrap_or(8);
+            let characteristics = u32_at(header, 36)?;
+
+            Ok(ImageSection {
+                name: String::from_utf8_lossy(&name[..name_length]).into(),
+                rva: u32_at(header, 12)? as usize,
+                size: u32_at(header, 8)? as usize,
+                executable: characteristics & IMAGE_SCN_MEM_EXECUTE != 0,
+                writable: characteristics & IMAGE_SCN_MEM_WRITE != 0,
+                data: data(
+                    file,
+                    u32_at(header, 20)? as usize,
+                    u32_at(header, 16)? as usize,
+                ),
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut image = Image {
+        format: Format::Pe,
+        mode,
+        image_base,
+        sections,
+        relocations: Vec::new(),
+    };
+
+    if directory_count > IMAGE_DIRECTORY_ENTRY_BASERELOC {
+        let directory = directories + IMAGE_DIRECTORY_ENTRY_BASERELOC * 8;
+        let rva = u32_at(file, directory)? as usize;
+        let size = u32_at(file, directory + 4)? as usize;
+
+        image.relocations = pe_relocations(&image, rva, size)?;
+    }
+
+    Ok(image)
+}
+
+/// Reads the base relocation blocks at `rva`, see `memory::module_relocations`.
+fn pe_relocations(
+    image: &Image<'_>,
+    rva: usize,
+    size: usize,
+) -> Result<Vec<(usize, usize)>, Error> {
+    let mut relocations = Vec::new();
+
+    let section = match image.section_at(rva) {
+        Some(section) if size > 0 => section,
+        _ => return Ok(relocations),
+    };
+
+    let blocks = data(section.data, rva - section.rva, size);
+    let mut block = 0;
+
+    while blocks.len().saturating_sub(block) >= 8 {
+        let page = u32_at(blocks, block)? as usize;
+        let block_size = u32_at(blocks, block + 4)? as usize;
+
+        if block_size < 8 {
+            break;
+        }
+
+        let end = add(block, block_size)?;
+
+        for entry in (block + 8..end.min(blocks.len())).step_by(2) {
+            let entry = u16_at(blocks, entry)?;
+
+            let size = match entry >> 12 {
+                IMAGE_REL_BASED_HIGHLOW => 4,
+                IMAGE_REL_BASED_DIR64 => 8,
+                _ => continue,
+            };
+
+            relocations.push((add(page, (entry & 0xFFF) as usize)?, size));
+        }
+
+        block = end;
+    }
+
+    Ok(relocations)
+}
+
+fn parse_elf(file: &[u8]) -> Result<Image<'_>, Error> {
+    if file.get(5) != Some(&1) {
+        return Err(invalid("not a little endian object"));
+    }
+
+    let wide = match file.get(4) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return Err(invalid("unknown ELF class")),
+    };
+
+    let mode = match (u16_at(file, 0x12)?, wide) {
+        (3, false) => Mode::X86,
+        (62, true) => Mode::X64,
+        _ => return Err(invalid("not an x86 object")),
+    };
+
+    let (headers, header_size, header_count) = match wide {
+        true => (
+            u64_at(file, 0x20)? as usize,
+            u16_at(file, 0x36)? as usize,
+            u16_at(file, 0x38)? as usize,
+        ),
+        false => (
+            u32_at(file, 0x1C)? as usize,
+            u16_at(file, 0x2A)? as usize,
+            u16_at(file, 0x2C)? as usize,
+        ),
+    };
+
+    let mut sections = Vec::new();
+
+    for i in 0..header_count {
+        let header = bytes(
+            file,
+            table_entry(headers, i, header_size)?,
+            if wide { 56 } else { 32 },
+        )?;
+
+        // p_type, p_flags, p_offset, p_vaddr, p_filesz and p_memsz
+        let (kind, flags, offset, address, file_size, memory_size) = match wide {
+            true => (
+                u32_at(header, 0)?,
+                u32_at(header, 4)?,
+                u64_at(header, 8)? as usize,
+                u64_at(header, 16)? as usize,
+                u64_at(header, 32)? as usize,
+                u64_at(header, 40)? as usize,
+            ),
+            false => (
+                u32_at(header, 0)?,
+                u32_at(header, 24)?,
+                u32_at(header, 4)? as usize,
+                u32_at(header, 8)? as usize,
+                u32_at(header, 16)? as usize,
+                u32_at(header, 20)? as usize,
+            ),
+        };
+
+        if kind != PT_LOAD {
+            continue;
+        }
+
+        let flag = |flag, name| if flags & flag != 0 { name } else { '-' };
+
+        sections.push(ImageSection {
+            name: [flag(PF_R, 'r'), flag(PF_W, 'w'), flag(PF_X, 'x')]
+                .iter()
+                .collect(),
+            rva: address,
+            size: memory_size,
+            executable: flags & PF_X != 0,
+            writable: flags & PF_W != 0,
+            data: data(file, offset, file_size),
+        });
+    }
+
+    Ok(Image {
+        format: Format::Elf,
+        mode,
+        image_base: 0,
+        sections,
+        relocations: Vec::new(),
+    })
+}
@@ -1,6 +1,7 @@
 /// Creates a function (getter and or setter) for a netvar.
-/// Safe to use, in case that the entity is null-ptr it will return
-/// the default value of the given return type.
+/// Safe to use, in case that the entity is null-ptr or the netvar does not
+/// exist it will return the default value of the given return type.
+/// The offset is looked up once per scan and cached in the function.
 ///
 /// # Examples
 ///
@@ -10,9 +11,29 @@
 ///     macros::netvar!("DT_BasePlayer", "m_iHealth", get_health, i32);
 ///     // instead lets also create a setter, its as simple as that!
 ///     macros::netvar!("DT_BasePlayer", "m_iHealth", get_health, set_health, i32);
+///     // arrays return a `NetvarArray`, which is empty if self.base is null.
+///     macros::netvar!("DT_BaseCombatCharacter", "m_hMyWeapons", get_weapons, [u32]);
 /// }
 /// ```
 macro_rules! netvar {
+    ($table:literal, $name:literal, $func_name:ident, [$element_type:ty]) => {
+        pub fn $func_name(&self) -> crate::netvar::NetvarArray<$element_type> {
+            use crate::netvar::{get_array, CachedArray, NetvarArray};
+
+            static LAYOUT: CachedArray = CachedArray::new();
+
+            if self.is_null() {
+                return NetvarArray::default();
+            }
+
+            let layout = some_or_ret!(
+                LAYOUT.get(|| get_array(obfstr!($table), obfstr!($name))),
+                NetvarArray::default()
+            );
+
+            unsafe { NetvarArray::new(self.as_ptr() as usize, layout) }
+        }
+    };
     ($table:literal, $name:literal, $func_name:ident, $return_type:ident) => {
         netvar!($table, $name, 0, $func_name, $return_type);
     };
@@ -21,38 +42,135 @@ macro_rules! netvar {
     };
     ($table:literal, $name:literal, $extra:expr, $func_name:ident, $return_type:ident) => {
         pub fn $func_name(&self) -> $return_type {
-            use crate::netvar::get_offset;
+            use crate::netvar::{get_offset, CachedOffset};
+
+            static OFFSET: CachedOffset = CachedOffset::new();
 
             if self.is_null() {
                 return $return_type::default();
             }
 
-            let value = self
-                .get_value::<$return_type>(get_offset(obfstr!($table), obfstr!($name)) + $extra);
-            return value;
+            let offset = some_or_ret!(
+                OFFSET.get(|| get_offset(obfstr!($table), obfstr!($name))),
+                $return_type::default()
+            );
+
+            self.get_value::<$return_type>(offset + $extra)
         }
     };
     ($table:literal, $name:literal, $extra:expr, $func_name:ident, $setter_name:ident, $return_type:ident) => {
         pub fn $func_name(&self) -> $return_type {
-            use crate::netvar::get_offset;
+            use crate::netvar::{get_offset, CachedOffset};
+
+            static OFFSET: CachedOffset = CachedOffset::new();
 
             if self.is_null() {
                 return $return_type::default();
             }
 
-            let value = self
-                .get_value::<$return_type>(get_offset(obfstr!($table), obfstr!($name)) + $extra);
-            return value;
+            let offset = some_or_ret!(
+                OFFSET.get(|| get_offset(obfstr!($table), obfstr!($name))),
+                $return_type::default()
+            );
+
+            self.get_value::<$return_type>(offset + $extra)
         }
 
         pub fn $setter_name(&self, state: $return_type) {
-            use crate::netvar::get_offset;
+            use crate::netvar::{get_offset, CachedOffset};
+
+            static OFFSET: CachedOffset = CachedOffset::new();
 
             if self.is_null() {
                 return;
             }
 
-            self.set_value(get_offset(obfstr!($table), obfstr!($name)) + $extra, state);
+            let offset = some_or_ret!(OFFSET.get(|| get_offset(obfstr!($table), obfstr!($name))));
+
+            self.set_value(offset + $extra, state);
+        }
+    };
+}
+
+/// Declares several netvars at once through [`netvar!`], taking the same arguments
+/// in parentheses, and registers them for `netvar::registry::declared`.
+///
+/// The type the macro is used in comes first, the registration runs before `main`
+/// and can't name `Self`.
+/// The macro is internal to the crate, so the example can't be run on its own.
+///
+/// # Examples
+///
+/// ```ignore
+/// impl MyEntity {
+///     netvars! {
+///         MyEntity;
+///         ("DT_BasePlayer", "m_iHealth", get_health, i32),
+///         ("DT_BaseEntity", "m_bSpotted", is_spotted, set_spotted, bool),
+///     }
+/// }
+///
+/// for declaration in MyEntity::declared_netvars() {
+///     declaration.check()?;
+/// }
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+macro_rules! netvars {
+    ($owner:ident; $(($($args:tt)*)),* $(,)?) => {
+        $(netvar!($($args)*);)*
+
+        /// Returns every netvar declared on this type.
+        pub(crate) fn declared_netvars() -> alloc::vec::Vec<crate::netvar::registry::NetvarDecl> {
+            #[static_init::constructor]
+            extern "C" fn register() {
+                crate::netvar::registry::register($owner::declared_netvars);
+            }
+
+            vec![$(netvar_decl!($($args)*)),*]
+        }
+    };
+}
+
+#[doc(hidden)]
+macro_rules! netvar_decl {
+    ($table:literal, $name:literal, $func_name:ident, [$element_type:ty]) => {
+        netvar_decl!(
+            $table,
+            $name,
+            0,
+            $func_name,
+            (crate::netvar::NetvarArray<$element_type>),
+            0
+        )
+    };
+    ($table:literal, $name:literal, $func_name:ident, $return_type:ident) => {
+        netvar_decl!($table, $name, 0, $func_name, $return_type)
+    };
+    ($table:literal, $name:literal, $func_name:ident, $setter_name:ident, $return_type:ident) => {
+        netvar_decl!($table, $name, 0, $func_name, $return_type)
+    };
+    ($table:literal, $name:literal, $extra:expr, $func_name:ident, $setter_name:ident, $return_type:ident) => {
+        netvar_decl!($table, $name, $extra, $func_name, $return_type)
+    };
+    ($table:literal, $name:literal, $extra:expr, $func_name:ident, $return_type:ident) => {
+        netvar_decl!(
+            $table,
+            $name,
+            $extra,
+            $func_name,
+            ($return_type),
+            core::mem::size_of::<$return_type>()
+        )
+    };
+    ($table:literal, $name:literal, $extra:expr, $func_name:ident, ($return_type:ty), $size:expr) => {
+        crate::netvar::registry::NetvarDecl {
+            owner: core::any::type_name::<Self>(),
+            accessor: stringify!($func_name),
+            table: obfstr!($table).into(),
+            name: obfstr!($name).into(),
+            extra: $extra,
+            prop_types: <$return_type as crate::netvar::registry::NetvarType>::PROP_TYPES,
+            size: $size,
         }
     };
 }
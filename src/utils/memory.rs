@@ -1,8 +1,13 @@
 //! Utility functions to interact with the game's memory.
 
-use crate::interface::Interface;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use libc::c_char;
 
+use crate::interface::Interface;
 use crate::utils::error::Error;
+use crate::utils::signature::Signature;
 #[doc(hidden)]
 #[cfg(target_os = "linux")]
 pub use linux::*;
@@ -38,11 +43,20 @@ impl<T: Interface> NotNull<T> {
 
 #[cfg(target_os = "windows")]
 mod windows {
-    use winapi::um::winnt::IMAGE_DOS_SIGNATURE;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::mem::size_of;
+
+    use super::Section;
+    use winapi::um::winnt::{
+        IMAGE_BASE_RELOCATION, IMAGE_DIRECTORY_ENTRY_BASERELOC, IMAGE_DOS_SIGNATURE,
+        IMAGE_FILE_HEADER, IMAGE_REL_BASED_DIR64, IMAGE_REL_BASED_HIGHLOW, IMAGE_SCN_MEM_EXECUTE,
+        IMAGE_SCN_MEM_WRITE, IMAGE_SECTION_HEADER,
+    };
     use winapi::{
         ctypes::{c_char, c_void},
-        shared::minwindef::HMODULE,
-        um::libloaderapi::{GetModuleHandleA, GetProcAddress},
+        shared::minwindef::{HMODULE, MAX_PATH},
+        um::libloaderapi::{GetModuleFileNameA, GetModuleHandleA, GetProcAddress},
         um::winnt::{PIMAGE_DOS_HEADER, PIMAGE_NT_HEADERS},
     };
 
@@ -83,79 +97,526 @@ mod windows {
         None
     }
 
-    /// Scans for a byte string in the given module.
-    ///
-    /// Will return `None` when [`get_module_handle()`] returns `null`, or
-    /// if it does not find the pattern.
-    /// # Examples
-    /// ```
-    /// // A '?' is represented by \x00
-    /// use sdk::utils::memory::pattern_scan;
+    /// Returns the address the given module was loaded at, RVAs are relative to it.
     ///
-    /// let result = pattern_scan("ntdll.dll\0".as_ptr() as _, b"\xFF\x00\xCD\xA9");
+    /// Returns `None` when [`get_module_handle()`] returns `null`.
+    pub fn module_base(module_name: *const c_char) -> Option<usize> {
+        let module = get_module_handle(module_name);
+
+        if module.is_null() {
+            return None;
+        }
+
+        Some(module as usize)
+    }
+
+    /// Returns the path the given module was loaded from.
     ///
-    /// assert_eq!(result, None)
-    /// ```
-    pub fn pattern_scan(module_name: *const c_char, sig: &[u8]) -> Option<*mut usize> {
+    /// Returns `None` when [`get_module_handle()`] returns `null`.
+    pub fn module_path(module_name: *const c_char) -> Option<String> {
         let module = get_module_handle(module_name);
 
         if module.is_null() {
             return None;
         }
 
+        let mut path = [0u8; MAX_PATH];
+        let length =
+            unsafe { GetModuleFileNameA(module, path.as_mut_ptr() as _, path.len() as u32) };
+
+        if length == 0 {
+            return None;
+        }
+
+        Some(String::from_utf8_lossy(&path[..length as usize]).into())
+    }
+
+    /// Returns the address and size of every value the loader relocated in the given module.
+    ///
+    /// Returns an empty `Vec` when [`get_module_handle()`] returns `null`.
+    pub fn module_relocations(module_name: *const c_char) -> Vec<(usize, usize)> {
+        let module = get_module_handle(module_name);
+
+        if module.is_null() {
+            return Vec::new();
+        }
+
         let dos_headers = unsafe { (module as PIMAGE_DOS_HEADER).read() };
 
         if dos_headers.e_magic != IMAGE_DOS_SIGNATURE {
-            return None;
+            return Vec::new();
         }
 
         let nt_headers = (module as usize + dos_headers.e_lfanew as usize) as PIMAGE_NT_HEADERS;
+        let directory = unsafe {
+            (*nt_headers).OptionalHeader.DataDirectory[IMAGE_DIRECTORY_ENTRY_BASERELOC as usize]
+        };
 
-        let size_of_image = (unsafe { *nt_headers }).OptionalHeader.SizeOfImage as usize;
-
-        let bytes = module as *mut u8;
+        if directory.VirtualAddress == 0 {
+            return Vec::new();
+        }
 
-        let size = sig.len();
+        let mut relocations = Vec::new();
+        let mut block = module as usize + directory.VirtualAddress as usize;
+        let end = block + directory.Size as usize;
 
-        for i in 0..(size_of_image - size as usize) {
-            let mut found = true;
+        // Every block holds the relocations of a 4 KiB page, the type is in the high 4 bits
+        // of each entry and the offset into the page in the low 12.
+        while block + size_of::<IMAGE_BASE_RELOCATION>() <= end {
+            let header = unsafe { (block as *const IMAGE_BASE_RELOCATION).read() };
+            let block_size = header.SizeOfBlock as usize;
 
-            for (j, byte) in sig.iter().enumerate().take(size) {
-                if unsafe { *bytes.offset(i as isize + j as isize) } != *byte && *byte != 0 {
-                    found = false;
-                    break;
-                }
+            if block_size < size_of::<IMAGE_BASE_RELOCATION>() {
+                break;
             }
 
-            if found {
-                return Some(unsafe { bytes.add(i) } as _);
+            let entries = (block_size - size_of::<IMAGE_BASE_RELOCATION>()) / size_of::<u16>();
+
+            for i in 0..entries {
+                let entry = unsafe {
+                    ((block + size_of::<IMAGE_BASE_RELOCATION>() + i * size_of::<u16>())
+                        as *const u16)
+                        .read()
+                };
+
+                let size = match entry >> 12 {
+                    IMAGE_REL_BASED_HIGHLOW => 4,
+                    IMAGE_REL_BASED_DIR64 => 8,
+                    _ => continue,
+                };
+
+                relocations.push((
+                    module as usize + header.VirtualAddress as usize + (entry & 0xFFF) as usize,
+                    size,
+                ));
             }
+
+            block += block_size;
         }
 
-        None
+        relocations
+    }
+
+    /// Returns the sections of the given module, e.g. `.text` and `.rdata`.
+    ///
+    /// Returns an empty `Vec` when [`get_module_handle()`] returns `null`.
+    pub fn module_sections(module_name: *const c_char) -> Vec<Section> {
+        let module = get_module_handle(module_name);
+
+        if module.is_null() {
+            return Vec::new();
+        }
+
+        let dos_headers = unsafe { (module as PIMAGE_DOS_HEADER).read() };
+
+        if dos_headers.e_magic != IMAGE_DOS_SIGNATURE {
+            return Vec::new();
+        }
+
+        let nt_headers = (module as usize + dos_headers.e_lfanew as usize) as PIMAGE_NT_HEADERS;
+        let file_header = unsafe { (*nt_headers).FileHeader };
+
+        // The section headers follow the optional header, which follows the signature and file header.
+        let first_section = nt_headers as usize
+            + size_of::<u32>()
+            + size_of::<IMAGE_FILE_HEADER>()
+            + file_header.SizeOfOptionalHeader as usize;
+
+        (0..file_header.NumberOfSections as usize)
+            .map(|i| {
+                let header = unsafe {
+                    ((first_section + i * size_of::<IMAGE_SECTION_HEADER>())
+                        as *const IMAGE_SECTION_HEADER)
+                        .read()
+                };
+
+                let name_length = header.Name.iter().position(|byte| *byte == 0).unwrap_or(8);
+
+                Section {
+                    name: String::from_utf8_lossy(&header.Name[..name_length]).into(),
+                    address: module as usize + header.VirtualAddress as usize,
+                    size: unsafe { *header.Misc.VirtualSize() } as usize,
+                    executable: header.Characteristics & IMAGE_SCN_MEM_EXECUTE != 0,
+                    writable: header.Characteristics & IMAGE_SCN_MEM_WRITE != 0,
+                }
+            })
+            .collect()
     }
 }
 
 #[cfg(target_os = "linux")]
 mod linux {
-    use libc::c_char;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use cstr_core::CStr;
+    use libc::{c_char, c_int, c_void, dl_iterate_phdr, dl_phdr_info};
+    use libc::{PF_R, PF_W, PF_X, PT_LOAD, RTLD_LOCAL, RTLD_NOLOAD, RTLD_NOW};
+
+    use super::Section;
+
+    /// Returns the `dlopen` handle of an already loaded module, `null()` if it isn't loaded.
+    ///
+    /// # Examples
+    /// ```
+    /// use sdk::utils::memory::get_module_handle;
+    ///
+    ///     let handle = get_module_handle("libc.so.6\0".as_ptr() as _);
+    ///
+    ///     assert!(!handle.is_null())
+    /// ```
+    pub fn get_module_handle(name: *const i8) -> *mut c_void {
+        loaded_handle(name)
+    }
 
-    /// Scans for a byte string in the given module.
+    /// Safe wrapper around `dlsym`.
     ///
-    /// Will return `None` when [`get_module_handle()`] returns `null`, or
-    /// if it does not find the pattern.
+    /// Returns `None` if `dlsym` returns `null()`.
     /// # Examples
     /// ```
-    /// // A '?' is represented by \x00
-    /// use sdk::utils::memory::pattern_scan;
+    /// use sdk::utils::memory::{get_proc_address, get_module_handle};
     ///
-    /// let result = pattern_scan("ntdll.dll\0".as_ptr() as _, b"\xFF\x00\xCD\xA9");
+    ///     let address = get_proc_address(
+    ///     get_module_handle("libc.so.6\0".as_ptr() as _), "printf\0".as_ptr() as _
+    ///     );
     ///
-    /// assert_eq!(result, None)
+    ///     assert!(address.is_some())
     /// ```
-    pub fn pattern_scan(_module_name: *const c_char, _sig: &[u8]) -> Option<*mut usize> {
+    pub fn get_proc_address(module: *mut c_void, name: *const i8) -> Option<*const c_void> {
+        if module.is_null() {
+            return None;
+        }
+
+        let result = symbol(module, name);
+
+        if !result.is_null() {
+            return Some(result as _);
+        }
+
         None
     }
+
+    /// Opens `name` only if it is already loaded, without keeping a reference.
+    fn loaded_handle(name: *const c_char) -> *mut c_void {
+        unsafe {
+            let handle = libc::dlopen(name, RTLD_NOLOAD | RTLD_NOW | RTLD_LOCAL);
+
+            // RTLD_NOLOAD still increments the reference count.
+            if !handle.is_null() {
+                libc::dlclose(handle);
+            }
+
+            handle
+        }
+    }
+
+    /// Looks `name` up in the `dlopen` handle `module`.
+    fn symbol(module: *mut c_void, name: *const c_char) -> *mut c_void {
+        unsafe { libc::dlsym(module, name) }
+    }
+
+    /// Returns `true` if the loaded object at `path` is `name`, which can be a file name
+    /// like `"engine_client.so"` or the end of a path like `"csgo/bin/linux64/client_client.so"`.
+    fn is_module(path: &[u8], name: &[u8]) -> bool {
+        path == name || path.ends_with(name) && path[path.len() - name.len() - 1] == b'/'
+    }
+
+    struct Search<'a> {
+        name: &'a [u8],
+        base: Option<usize>,
+        path: Option<String>,
+        sections: Vec<Section>,
+    }
+
+    unsafe extern "C" fn collect_sections(
+        info: *mut dl_phdr_info,
+        _size: usize,
+        data: *mut c_void,
+    ) -> c_int {
+        let info = &*info;
+        let search = &mut *(data as *mut Search);
+
+        if info.dlpi_name.is_null() {
+            return 0;
+        }
+
+        let path = CStr::from_ptr(info.dlpi_name).to_bytes();
+
+        if !is_module(path, search.name) {
+            return 0;
+        }
+
+        search.base = Some(info.dlpi_addr as usize);
+        search.path = Some(String::from_utf8_lossy(path).into());
+
+        for i in 0..info.dlpi_phnum as usize {
+            let header = &*info.dlpi_phdr.add(i);
+
+            if header.p_type != PT_LOAD {
+                continue;
+            }
+
+            let flag = |flag, name| {
+                if header.p_flags & flag != 0 {
+                    name
+                } else {
+                    '-'
+                }
+            };
+
+            search.sections.push(Section {
+                name: [flag(PF_R, 'r'), flag(PF_W, 'w'), flag(PF_X, 'x')]
+                    .iter()
+                    .collect(),
+                address: info.dlpi_addr as usize + header.p_vaddr as usize,
+                size: header.p_memsz as usize,
+                executable: header.p_flags & PF_X != 0,
+                writable: header.p_flags & PF_W != 0,
+            });
+        }
+
+        1
+    }
+
+    /// Returns the base, the path and the segments of the given module.
+    fn search(module_name: *const c_char) -> (Option<usize>, Option<String>, Vec<Section>) {
+        if module_name.is_null() {
+            return (None, None, Vec::new());
+        }
+
+        let mut search = Search {
+            name: unsafe { CStr::from_ptr(module_name) }.to_bytes(),
+            base: None,
+            path: None,
+            sections: Vec::new(),
+        };
+
+        unsafe {
+            dl_iterate_phdr(
+                Some(collect_sections),
+                &mut search as *mut Search as *mut c_void,
+            )
+        };
+
+        (search.base, search.path, search.sections)
+    }
+
+    /// Returns the path the given module was loaded from.
+    ///
+    /// Returns `None` if no loaded object is called `module_name`.
+    /// # Examples
+    /// ```
+    /// use sdk::utils::memory::module_path;
+    ///
+    /// let path = module_path("libc.so.6\0".as_ptr() as _).unwrap();
+    ///
+    /// assert!(path.ends_with("/libc.so.6"));
+    /// ```
+    pub fn module_path(module_name: *const c_char) -> Option<String> {
+        search(module_name).1
+    }
+
+    /// Returns the address and size of every value the loader relocated in the executable
+    /// segments of the given module.
+    ///
+    /// Always empty, the game's modules are position independent and their code is never
+    /// relocated.
+    pub fn module_relocations(_module_name: *const c_char) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
+
+    /// Returns the load bias of the given module, RVAs are relative to it.
+    ///
+    /// Returns `None` if no loaded object is called `module_name`.
+    pub fn module_base(module_name: *const c_char) -> Option<usize> {
+        search(module_name).0
+    }
+
+    /// Returns the `PT_LOAD` segments of the given module.
+    ///
+    /// Segments have no names, so they are named after their permissions, e.g. `r-x`.
+    /// Returns an empty `Vec` if no loaded object is called `module_name`.
+    pub fn module_sections(module_name: *const c_char) -> Vec<Section> {
+        search(module_name).2
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const LIBC: *const c_char = b"libc.so.6\0".as_ptr() as _;
+        const MISSING: *const c_char = b"missing.so\0".as_ptr() as _;
+
+        #[test]
+        fn libc_sections() {
+            let base = module_base(LIBC).unwrap();
+            let sections = module_sections(LIBC);
+            let getpid = libc::getpid as *const () as usize;
+
+            assert!(sections.iter().all(|section| section.address >= base));
+            assert!(sections
+                .iter()
+                .any(|section| section.name == "r-x" && section.executable));
+
+            let code = sections
+                .iter()
+                .find(|section| {
+                    getpid >= section.address && getpid < section.address + section.size
+                })
+                .unwrap();
+
+            assert!(code.executable && !code.writable);
+        }
+
+        #[test]
+        fn libc_path() {
+            assert!(module_path(LIBC).unwrap().ends_with("/libc.so.6"));
+        }
+
+        #[test]
+        fn missing_module() {
+            assert_eq!(module_base(MISSING), None);
+            assert_eq!(module_path(MISSING), None);
+            assert!(module_sections(MISSING).is_empty());
+            assert!(module_sections(core::ptr::null()).is_empty());
+        }
+
+        #[test]
+        fn module_names() {
+            assert!(is_module(b"libc.so.6", b"libc.so.6"));
+            assert!(is_module(b"/usr/lib/libc.so.6", b"libc.so.6"));
+            assert!(is_module(
+                b"/games/csgo/bin/linux64/client_client.so",
+                b"csgo/bin/linux64/client_client.so"
+            ));
+            assert!(!is_module(b"/usr/lib/xlibc.so.6", b"libc.so.6"));
+            assert!(!is_module(b"libc.so", b"libc.so.6"));
+        }
+    }
+}
+
+/// A part of a loaded module, a PE section on Windows or a `PT_LOAD` segment on Linux.
+#[derive(Clone, Debug)]
+pub struct Section {
+    pub name: String,
+    pub address: usize,
+    pub size: usize,
+    pub executable: bool,
+    pub writable: bool,
+}
+
+impl Section {
+    /// Returns the memory of the section.
+    /// # Safety
+    /// The module has to stay loaded while the slice is used.
+    pub unsafe fn bytes(&self) -> &'static [u8] {
+        core::slice::from_raw_parts(self.address as *const u8, self.size)
+    }
+}
+
+/// Which sections of a module a scan looks at.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum Sections<'a> {
+    /// Code only, `.text` on Windows and `r-x` segments on Linux.
+    #[default]
+    Executable,
+    /// The sections with the given name, e.g. `.rdata`.
+    Named(&'a str),
+    All,
+}
+
+impl Sections<'_> {
+    pub(crate) fn includes(&self, section: &Section) -> bool {
+        match self {
+            Sections::Executable => section.executable,
+            Sections::Named(name) => section.name == *name,
+            Sections::All => true,
+        }
+    }
+}
+
+/// Scans for a signature in the executable sections of the given module.
+///
+/// Will return `None` if the module is not loaded, or if it does not find the pattern.
+/// # Examples
+/// ```no_run
+/// use sdk::signature;
+/// use sdk::utils::memory::pattern_scan;
+/// use sdk::utils::platform::modules;
+///
+/// let result = pattern_scan(modules::CLIENT, &signature!("0F 11 05 ? ? ? ? 83 C8 01"));
+/// ```
+pub fn pattern_scan(module_name: *const c_char, sig: &Signature) -> Option<*mut usize> {
+    pattern_scan_in(module_name, sig, Sections::Executable)
+}
+
+/// Same as [`pattern_scan`], but scans the given sections.
+pub fn pattern_scan_in(
+    module_name: *const c_char,
+    sig: &Signature,
+    sections: Sections,
+) -> Option<*mut usize> {
+    module_sections(module_name)
+        .iter()
+        .filter(|section| sections.includes(section))
+        .find_map(|section| {
+            sig.find(unsafe { section.bytes() })
+                .map(|offset| (section.address + offset) as *mut usize)
+        })
+}
+
+/// Returns every match of the signature in the given sections, in ascending order.
+/// # Examples
+/// ```
+/// use sdk::utils::memory::{pattern_scan_all, Sections};
+/// use sdk::utils::signature::Signature;
+///
+/// // the first bytes of a function in libc have to be found in libc.
+/// let function = libc::getpid as usize as *const u8;
+/// let bytes = unsafe { std::slice::from_raw_parts(function, 8) }.to_vec();
+/// let signature = Signature::new(bytes, vec![true; 8])?;
+///
+/// let matches = pattern_scan_all("libc.so.6\0".as_ptr() as _, &signature, Sections::Executable);
+///
+/// assert!(matches.contains(&(function as *mut usize)));
+/// # Ok::<(), sdk::utils::error::Error>(())
+/// ```
+pub fn pattern_scan_all(
+    module_name: *const c_char,
+    sig: &Signature,
+    sections: Sections,
+) -> Vec<*mut usize> {
+    let mut sections_to_scan = module_sections(module_name);
+    sections_to_scan.retain(|section| sections.includes(section));
+    sections_to_scan.sort_by_key(|section| section.address);
+
+    sections_to_scan
+        .iter()
+        .flat_map(|section| {
+            sig.find_iter(unsafe { section.bytes() })
+                .map(move |offset| (section.address + offset) as *mut usize)
+        })
+        .collect()
+}
+
+/// Same as [`pattern_scan`], but fails unless the signature matches exactly once.
+///
+/// Use this to make sure a signature still identifies a single place after an update.
+pub fn pattern_scan_unique(
+    module_name: *const c_char,
+    sig: &Signature,
+) -> Result<*mut usize, Error> {
+    let matches = pattern_scan_all(module_name, sig, Sections::Executable);
+
+    match matches.as_slice() {
+        [address] => Ok(*address),
+        [] => Err(Error::NotFound {
+            item: format!("{}", sig),
+        }),
+        _ => Err(Error::Other {
+            msg: format!("{} matches {} times", sig, matches.len()),
+        }),
+    }
 }
 
 /// Get the address of a virtual function by its vtable-ptr and the index.
@@ -2,12 +2,20 @@
 
 #[macro_use]
 pub(crate) mod macros;
+pub mod batch;
 pub mod error;
+pub mod image;
 pub mod math;
 pub mod memory;
-pub(crate) mod patterns;
 pub mod platform;
+pub mod resolve;
+pub mod signature;
+pub mod signature_cache;
+pub mod signature_db;
+pub mod signature_gen;
 pub mod string;
+pub mod x86;
+pub mod xref;
 
 pub(crate) fn variant_eq<T>(a: T, b: T) -> bool {
     core::mem::discriminant(&a) == core::mem::discriminant(&b)
@@ -17,6 +17,24 @@ pub use linux as modules;
 #[cfg(target_os = "windows")]
 pub use windows as modules;
 
+/// Returns the module of this platform named like its constant in lowercase, e.g. `"client"`
+/// for [`modules::CLIENT`].
+pub fn module_by_name(name: &str) -> Option<*const i8> {
+    Some(match name {
+        "client" => modules::CLIENT,
+        "engine" => modules::ENGINE,
+        "tier0" => modules::TIER0,
+        "vgui" => modules::VGUI,
+        "vgui2" => modules::VGUI2,
+        "input_system" => modules::INPUT_SYSTEM,
+        "vstd_lib" => modules::VSTD_LIB,
+        "material_system" => modules::MATERIAL_SYSTEM,
+        "localize" => modules::LOCALIZE,
+        "physics" => modules::PHYSICS,
+        _ => return None,
+    })
+}
+
 pub mod windows {
     //! Module names for windows
     pub const CLIENT: *const i8 = cstr!("client.dll");
@@ -0,0 +1,333 @@
+//! Turning the address of a signature match into the address that is actually wanted.
+//!
+//! A [`Chain`] lists the pointer math that would otherwise be written by hand after
+//! every scan, e.g. skipping to an operand and reading the pointer stored there.
+//! # Examples
+//! ```no_run
+//! use sdk::signature;
+//! use sdk::utils::platform::modules;
+//! use sdk::utils::resolve::{Chain, Pattern, Step};
+//!
+//! // mov ecx, offset g_Input
+//! const INPUT: Pattern = Pattern {
+//!     signature: signature!("B9 ? ? ? ? 8B 40 38 FF D0 84 C0 0F 85"),
+//!     chain: Chain::new(&[Step::Offset(0x1), Step::Deref]),
+//! };
+//!
+//! let input = INPUT.scan(modules::CLIENT, "IInput")?;
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+
+use libc::c_char;
+
+use crate::utils::error::Error;
+use crate::utils::memory::pattern_scan;
+use crate::utils::signature::Signature;
+
+/// A single step of a [`Chain`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Step {
+    /// Moves forward, e.g. from the start of a match to an operand of one of its instructions.
+    Offset(usize),
+    /// Reads the pointer at the address, which must not be null.
+    Deref,
+    /// Follows the 32 bit displacement at the address, relative to the end of it.
+    ///
+    /// This is how `rip` relative operands and `rel32` branch targets are encoded.
+    Rel32,
+    /// Decodes the target of the `call rel32` or `jmp rel32` instruction at the address.
+    Call,
+    /// Adds a signed value, e.g. the offset of a member to the pointer of its object.
+    Add(isize),
+}
+
+impl Step {
+    fn parse(text: &str) -> Option<Self> {
+        let mut words = text.split_whitespace();
+        let step = match (words.next()?, words.next()) {
+            ("offset", Some(offset)) => Step::Offset(parse_hex(offset)?),
+            ("deref", None) => Step::Deref,
+            ("rel32", None) => Step::Rel32,
+            ("call", None) => Step::Call,
+            ("add", Some(value)) => match value.strip_prefix('-') {
+                Some(value) => Step::Add(-(parse_hex(value)? as isize)),
+                None => Step::Add(parse_hex(value)? as isize),
+            },
+            _ => return None,
+        };
+
+        match words.next() {
+            Some(_) => None,
+            None => Some(step),
+        }
+    }
+}
+
+fn parse_hex(text: &str) -> Option<usize> {
+    usize::from_str_radix(text.strip_prefix("0x")?, 16)
+        .ok()
+        .filter(|value| *value <= isize::MAX as usize)
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Offset(offset) => write!(f, "offset 0x{:X}", offset),
+            Step::Deref => write!(f, "deref"),
+            Step::Rel32 => write!(f, "rel32"),
+            Step::Call => write!(f, "call"),
+            Step::Add(value) if *value < 0 => write!(f, "add -0x{:X}", value.unsigned_abs()),
+            Step::Add(value) => write!(f, "add 0x{:X}", value),
+        }
+    }
+}
+
+/// Steps that are applied to an address one after another.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Chain {
+    steps: Cow<'static, [Step]>,
+}
+
+impl Chain {
+    pub const fn new(steps: &'static [Step]) -> Self {
+        Self {
+            steps: Cow::Borrowed(steps),
+        }
+    }
+
+    pub fn from_steps(steps: Vec<Step>) -> Self {
+        Self {
+            steps: Cow::Owned(steps),
+        }
+    }
+
+    /// Parses steps separated by commas, as written by `to_string`, e.g. `"offset 0x3, deref"`.
+    ///
+    /// `-` is a chain without steps.
+    /// # Examples
+    /// ```
+    /// use sdk::utils::resolve::{Chain, Step};
+    ///
+    /// let chain = Chain::parse("offset 0x2, call, add -0x10")?;
+    ///
+    /// assert_eq!(chain.steps(), &[Step::Offset(0x2), Step::Call, Step::Add(-0x10)]);
+    /// assert_eq!(Chain::parse(&chain.to_string())?, chain);
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        if text.trim() == "-" {
+            return Ok(Self::default());
+        }
+
+        text.split(',')
+            .map(|step| {
+                Step::parse(step.trim()).ok_or_else(|| Error::Other {
+                    msg: format!("invalid step {:?} in {:?}", step.trim(), text),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self::from_steps)
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Applies every step to `address`.
+    ///
+    /// Fails with `Error::Resolve`, naming `item` and the step, if a step would read from
+    /// null, produce null, overflow or finds no `call`/`jmp` where it expects one.
+    /// # Safety
+    /// Every address that is read from has to be readable.
+    /// # Examples
+    /// ```
+    /// use sdk::utils::resolve::{Chain, Step};
+    ///
+    /// // call +0x10, followed by the bytes of the target.
+    /// let code = [0xE8u8, 0x10, 0x00, 0x00, 0x00];
+    /// let address = code.as_ptr() as usize;
+    ///
+    /// let target = unsafe { Chain::new(&[Step::Call]).resolve("example", address) }?;
+    ///
+    /// assert_eq!(target, address + 5 + 0x10);
+    /// assert!(unsafe { Chain::new(&[Step::Offset(1), Step::Call]).resolve("example", address) }.is_err());
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    pub unsafe fn resolve(&self, item: &str, address: usize) -> Result<usize, Error> {
+        let mut address = address;
+
+        for (index, step) in self.steps.iter().enumerate() {
+            address = apply(*step, address).map_err(|reason| Error::Resolve {
+                item: item.into(),
+                step: format!("{} ({})", index + 1, step),
+                reason: reason.into(),
+            })?;
+        }
+
+        Ok(address)
+    }
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.steps.is_empty() {
+            return write!(f, "-");
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}", step)?;
+        }
+
+        Ok(())
+    }
+}
+
+unsafe fn apply(step: Step, address: usize) -> Result<usize, &'static str> {
+    if address == 0 {
+        return Err("the address is null");
+    }
+
+    let relative = |address: usize| {
+        let displacement = (address as *const i32).read_unaligned() as isize;
+
+        (address + 4)
+            .checked_add_signed(displacement)
+            .ok_or("the target is out of range")
+    };
+
+    let result = match step {
+        Step::Offset(offset) => address.checked_add(offset).ok_or("the offset overflows")?,
+        Step::Deref => (address as *const usize).read_unaligned(),
+        Step::Rel32 => relative(address)?,
+        Step::Call => match (address as *const u8).read() {
+            0xE8 | 0xE9 => relative(address + 1)?,
+            _ => return Err("there is no call or jmp"),
+        },
+        Step::Add(value) => address
+            .checked_add_signed(value)
+            .ok_or("the addition overflows")?,
+    };
+
+    if result == 0 {
+        return Err("the result is null");
+    }
+
+    Ok(result)
+}
+
+/// A signature together with the chain that turns its match into the wanted address.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pattern {
+    pub signature: Signature,
+    pub chain: Chain,
+}
+
+impl Pattern {
+    /// Resolves the chain starting at `address`, the address of a match.
+    /// # Safety
+    /// See [`Chain::resolve`].
+    pub unsafe fn resolve(&self, item: &str, address: usize) -> Result<usize, Error> {
+        self.chain.resolve(item, address)
+    }
+
+    /// Scans for the signature in the given module and resolves the chain from the match.
+    pub fn scan(&self, module_name: *const c_char, item: &str) -> Result<usize, Error> {
+        let address = pattern_scan(module_name, &self.signature)
+            .ok_or_else(|| Error::NotFound { item: item.into() })?;
+
+        unsafe { self.resolve(item, address as usize) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(steps: &'static [Step], address: usize) -> Result<usize, Error> {
+        unsafe { Chain::new(steps).resolve("test", address) }
+    }
+
+    #[test]
+    fn offset() {
+        assert_eq!(resolve(&[Step::Offset(0x10)], 0x1000).unwrap(), 0x1010);
+        assert!(resolve(&[Step::Offset(0x10)], usize::MAX - 8).is_err());
+    }
+
+    #[test]
+    fn deref() {
+        let target = 0x1234_5678_usize;
+        let pointers = [0, target];
+        let address = pointers.as_ptr() as usize;
+
+        assert_eq!(
+            resolve(
+                &[Step::Offset(core::mem::size_of::<usize>()), Step::Deref],
+                address
+            )
+            .unwrap(),
+            target
+        );
+        // The first pointer is null.
+        assert!(resolve(&[Step::Deref], address).is_err());
+        assert!(resolve(&[Step::Deref], 0).is_err());
+    }
+
+    #[test]
+    fn rel32() {
+        // mov eax, [rip + 0x20]; mov eax, [rip - 0x10]
+        let code = [
+            0x8Bu8, 0x05, 0x20, 0, 0, 0, 0x8B, 0x05, 0xF0, 0xFF, 0xFF, 0xFF,
+        ];
+        let address = code.as_ptr() as usize;
+
+        assert_eq!(
+            resolve(&[Step::Offset(2), Step::Rel32], address).unwrap(),
+            address + 6 + 0x20
+        );
+        assert_eq!(
+            resolve(&[Step::Offset(8), Step::Rel32], address).unwrap(),
+            address + 12 - 0x10
+        );
+    }
+
+    #[test]
+    fn call() {
+        // call -0x20; jmp +0x40; nop
+        let code = [0xE8u8, 0xE0, 0xFF, 0xFF, 0xFF, 0xE9, 0x40, 0, 0, 0, 0x90];
+        let address = code.as_ptr() as usize;
+
+        assert_eq!(resolve(&[Step::Call], address).unwrap(), address + 5 - 0x20);
+        assert_eq!(
+            resolve(&[Step::Offset(5), Step::Call], address).unwrap(),
+            address + 10 + 0x40
+        );
+        assert!(resolve(&[Step::Offset(10), Step::Call], address).is_err());
+    }
+
+    #[test]
+    fn add() {
+        assert_eq!(resolve(&[Step::Add(0x10)], 0x1000).unwrap(), 0x1010);
+        assert_eq!(resolve(&[Step::Add(-0x10)], 0x1000).unwrap(), 0xFF0);
+        assert!(resolve(&[Step::Add(-0x1000)], 0x1000).is_err());
+        assert!(resolve(&[Step::Add(-0x2000)], 0x1000).is_err());
+    }
+
+    #[test]
+    fn failing_step() {
+        match resolve(&[Step::Add(0x10), Step::Add(-0x1010)], 0x1000) {
+            Err(Error::Resolve { item, step, .. }) => {
+                assert_eq!(item, "test");
+                assert_eq!(step, "2 (add -0x1010)");
+            }
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+}
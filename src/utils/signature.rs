@@ -0,0 +1,387 @@
+//! Byte signatures with wildcards, written like in IDA.
+//!
+//! Every byte is written as two hex digits, a `?` or `??` matches any byte.
+//! # Examples
+//! ```
+//! use sdk::signature;
+//! use sdk::utils::signature::Signature;
+//!
+//! // checked at compile time, an invalid byte fails to build.
+//! const INPUT_INTERFACE: Signature = signature!("B9 ? ? ? ? 8B 40 38");
+//!
+//! let parsed = Signature::parse("B9 ? ? ? ? 8B 40 38")?;
+//!
+//! assert_eq!(parsed, INPUT_INTERFACE);
+//! assert_eq!(parsed.find(b"\x90\xB9\x00\x00\x00\x00\x8B\x40\x38"), Some(1));
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+
+use memchr::memchr_iter;
+
+use crate::utils::error::Error;
+
+/// Bytes too common in machine code to search for first: padding, `mov` and the bytes
+/// of small and negative operands.
+const COMMON_BYTES: &[u8] = &[0x00, 0xFF, 0xCC, 0x8B];
+
+/// A byte pattern where `mask[i]` tells whether `bytes[i]` has to match.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Signature {
+    bytes: Cow<'static, [u8]>,
+    mask: Cow<'static, [bool]>,
+}
+
+impl Signature {
+    /// Creates a signature from bytes and a mask of the same length, used by [`signature!`].
+    pub const fn from_static(bytes: &'static [u8], mask: &'static [bool]) -> Self {
+        assert!(bytes.len() == mask.len(), "bytes and mask differ in length");
+
+        let mut i = 0;
+
+        while i < mask.len() && !mask[i] {
+            i += 1;
+        }
+
+        assert!(i < mask.len(), "signature has no concrete bytes");
+
+        Self {
+            bytes: Cow::Borrowed(bytes),
+            mask: Cow::Borrowed(mask),
+        }
+    }
+
+    /// Creates a signature from bytes and a mask of the same length.
+    ///
+    /// At least one byte has to be concrete.
+    pub fn new(bytes: Vec<u8>, mask: Vec<bool>) -> Result<Self, Error> {
+        if bytes.len() != mask.len() {
+            return Err(Error::Other {
+                msg: format!(
+                    "signature has {} bytes but a mask of {}",
+                    bytes.len(),
+                    mask.len()
+                ),
+            });
+        }
+
+        if !mask.contains(&true) {
+            return Err(Error::Other {
+                msg: "signature has no concrete bytes".into(),
+            });
+        }
+
+        Ok(Self {
+            bytes: Cow::Owned(bytes),
+            mask: Cow::Owned(mask),
+        })
+    }
+
+    /// Parses a signature like `"B9 ? ? ? ? 8B 40 38"`.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+
+        for token in text.split_whitespace() {
+            match parse_token(token.as_bytes(), 0, token.len()) {
+                Token::Byte(byte) => {
+                    bytes.push(byte);
+                    mask.push(true);
+                }
+                Token::Wildcard => {
+                    bytes.push(0);
+                    mask.push(false);
+                }
+                Token::Invalid => {
+                    return Err(Error::Other {
+                        msg: format!("invalid signature byte {:?} in {:?}", token, text),
+                    })
+                }
+            }
+        }
+
+        Self::new(bytes, mask)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The bytes of the signature, wildcards are `0`.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// `true` for every byte that has to match, `false` for wildcards.
+    pub fn mask(&self) -> &[bool] {
+        &self.mask
+    }
+
+    /// Returns `true` if `data` starts with the signature.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.len()
+            && self
+                .bytes
+                .iter()
+                .zip(self.mask.iter())
+                .zip(data)
+                .all(|((byte, concrete), data)| !concrete || byte == data)
+    }
+
+    /// Returns the index of the first concrete byte that isn't one of the most common bytes
+    /// in machine code, or of the first concrete byte if all of them are.
+    ///
+    /// Scans look for this byte first and only compare the rest where it was found.
+    pub fn anchor(&self) -> usize {
+        (0..self.len())
+            .filter(|i| self.mask[*i])
+            .min_by_key(|i| COMMON_BYTES.contains(&self.bytes[*i]))
+            .unwrap_or(0)
+    }
+
+    /// Returns the offset of the first match in `haystack`.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        self.find_iter(haystack).next()
+    }
+
+    /// Returns the offsets of all matches in `haystack`.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<usize> {
+        self.find_iter(haystack).collect()
+    }
+
+    /// Iterates over the offsets of all matches in `haystack`, in ascending order.
+    pub fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let anchor = self.anchor();
+
+        // Every match starts at the position the anchor was found at in this slice.
+        let candidates = match haystack.len().checked_sub(self.len()) {
+            Some(last) if !self.is_empty() => &haystack[anchor..=last + anchor],
+            _ => &[],
+        };
+
+        memchr_iter(self.bytes.get(anchor).copied().unwrap_or(0), candidates)
+            .filter(move |start| self.matches(&haystack[*start..]))
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = self.bytes.iter().zip(self.mask.iter()).peekable();
+
+        while let Some((byte, concrete)) = tokens.next() {
+            match concrete {
+                true => write!(f, "{:02X}", byte)?,
+                false => write!(f, "?")?,
+            }
+
+            if tokens.peek().is_some() {
+                write!(f, " ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum Token {
+    Byte(u8),
+    Wildcard,
+    Invalid,
+}
+
+const fn hex_digit(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        b'A'..=b'F' => Some(digit - b'A' + 10),
+        _ => None,
+    }
+}
+
+const fn parse_token(text: &[u8], start: usize, end: usize) -> Token {
+    match end - start {
+        1 if text[start] == b'?' => Token::Wildcard,
+        2 if text[start] == b'?' && text[start + 1] == b'?' => Token::Wildcard,
+        2 => match (hex_digit(text[start]), hex_digit(text[start + 1])) {
+            (Some(high), Some(low)) => Token::Byte(high << 4 | low),
+            _ => Token::Invalid,
+        },
+        _ => Token::Invalid,
+    }
+}
+
+/// Returns the number of bytes in `text`, used by [`signature!`].
+#[doc(hidden)]
+pub const fn count(text: &str) -> usize {
+    let text = text.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        if text[i] != b' ' && (i == 0 || text[i - 1] == b' ') {
+            count += 1;
+        }
+
+        i += 1;
+    }
+
+    count
+}
+
+/// Parses `text` at compile time, used by [`signature!`].
+///
+/// Panics, failing the build, if `text` has an invalid byte or only wildcards.
+#[doc(hidden)]
+pub const fn parse_const<const N: usize>(text: &str) -> ([u8; N], [bool; N]) {
+    let text = text.as_bytes();
+    let mut bytes = [0; N];
+    let mut mask = [false; N];
+    let mut concrete = false;
+    let mut index = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        if text[i] == b' ' {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+
+        while i < text.len() && text[i] != b' ' {
+            i += 1;
+        }
+
+        match parse_token(text, start, i) {
+            Token::Byte(byte) => {
+                bytes[index] = byte;
+                mask[index] = true;
+                concrete = true;
+            }
+            Token::Wildcard => {}
+            Token::Invalid => panic!("invalid signature byte"),
+        }
+
+        index += 1;
+    }
+
+    if !concrete {
+        panic!("signature has no concrete bytes");
+    }
+
+    (bytes, mask)
+}
+
+/// Creates a [`Signature`] from text like `"B9 ? ? ? ? 8B 40 38"`, checked at compile time.
+///
+/// Bytes have to be separated by spaces.
+#[macro_export]
+macro_rules! signature {
+    ($text:literal) => {{
+        const LENGTH: usize = $crate::utils::signature::count($text);
+        const PARSED: ([u8; LENGTH], [bool; LENGTH]) =
+            $crate::utils::signature::parse_const::<LENGTH>($text);
+        const BYTES: [u8; LENGTH] = PARSED.0;
+        const MASK: [bool; LENGTH] = PARSED.1;
+
+        $crate::utils::signature::Signature::from_static(&BYTES, &MASK)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn parse_tokens() {
+        let signature = Signature::parse(" B9 ? ??\t8b  40 ").unwrap();
+
+        assert_eq!(signature.bytes(), &[0xB9, 0, 0, 0x8B, 0x40]);
+        assert_eq!(signature.mask(), &[true, false, false, true, true]);
+        assert_eq!(signature.to_string(), "B9 ? ? 8B 40");
+        assert_eq!(Signature::parse(&signature.to_string()).unwrap(), signature);
+    }
+
+    #[test]
+    fn invalid_tokens() {
+        for text in &["B", "B9A", "G0", "?0", "???", "B9 ?? -", "", "? ??"] {
+            assert!(Signature::parse(text).is_err(), "{:?}", text);
+        }
+
+        assert!(Signature::new(vec![0xB9, 0x00], vec![true]).is_err());
+    }
+
+    #[test]
+    fn compile_time_parsing() {
+        assert_eq!(count(""), 0);
+        assert_eq!(count("B9"), 1);
+        assert_eq!(count("  B9   ??  8B "), 3);
+        assert_eq!(
+            parse_const::<4>("B9 ? ?? 8b"),
+            ([0xB9, 0, 0, 0x8B], [true, false, false, true])
+        );
+        assert_eq!(
+            signature!("B9 ? ?? 8b"),
+            Signature::parse("B9 ? ?? 8b").unwrap()
+        );
+    }
+
+    #[test]
+    fn find_edges() {
+        let signature = Signature::parse("8B ? 38").unwrap();
+
+        assert_eq!(signature.find(b""), None);
+        assert_eq!(signature.find(b"\x8B\x40"), None);
+        assert_eq!(signature.find(b"\x8B\x40\x38"), Some(0));
+        assert_eq!(signature.find(b"\x90\x8B\x8B\x40\x38"), Some(2));
+        assert_eq!(signature.find(b"\x8B\x40\x39\x90"), None);
+
+        // A trailing wildcard still needs a byte to match.
+        let signature = Signature::parse("38 ?").unwrap();
+
+        assert_eq!(signature.find(b"\x90\x38"), None);
+        assert_eq!(signature.find(b"\x90\x38\x00"), Some(1));
+    }
+
+    #[test]
+    fn find_all_matches() {
+        let signature = Signature::parse("AA ? AA").unwrap();
+
+        assert_eq!(signature.find_all(&[0xAA; 5]), vec![0, 1, 2]);
+        assert_eq!(signature.find_all(&[0xAA; 2]), vec![]);
+        assert_eq!(signature.find_all(b""), vec![]);
+        assert_eq!(signature.find_all(b"\xAA\x00\xAA\x00\xAB"), vec![0]);
+    }
+
+    #[test]
+    fn anchor_at_the_last_byte() {
+        let signature = Signature::parse("? ? 38").unwrap();
+
+        assert_eq!(signature.anchor(), 2);
+        assert_eq!(signature.find_all(b"\x38\x38\x38\x38"), vec![0, 1]);
+        assert_eq!(signature.find(b"\x38\x38"), None);
+        assert_eq!(signature.find(b"\x90\x90\x38\x90"), Some(0));
+    }
+
+    #[test]
+    fn anchor_skips_common_bytes() {
+        assert_eq!(Signature::parse("8B 00 ? 45 12").unwrap().anchor(), 3);
+        assert_eq!(Signature::parse("? FF 8B CC 00").unwrap().anchor(), 1);
+
+        let signature = Signature::parse("00 00 8B 45 ? 38").unwrap();
+
+        assert_eq!(
+            signature.find_all(b"\x00\x00\x00\x8B\x45\x00\x38\x00\x00\x8B\x45"),
+            vec![1]
+        );
+    }
+}
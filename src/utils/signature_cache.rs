@@ -0,0 +1,307 @@
+//! Remembers where signatures were found, so they don't have to be scanned for again.
+//!
+//! Matches are stored as RVAs together with a checksum of the executable sections of their
+//! module. As long as the checksum is the same and the signature still matches at the RVA,
+//! [`SignatureDb::resolve_all_cached`](crate::utils::signature_db::SignatureDb::resolve_all_cached)
+//! uses the cached match instead of scanning.
+//! # Examples
+//! ```no_run
+//! use sdk::interface::LoadOptions;
+//! use sdk::utils::signature_cache::SignatureCache;
+//!
+//! let report = sdk::initialize_with(&LoadOptions {
+//!     signature_cache: Some(SignatureCache::load("signatures.cache").unwrap_or_default()),
+//!     ..LoadOptions::default()
+//! })?;
+//!
+//! if let Some(cache) = &report.signature_cache {
+//!     cache.save("signatures.cache")?;
+//! }
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use libc::c_char;
+
+use crate::utils::error::Error;
+use crate::utils::memory::{module_relocations, module_sections, Section};
+
+const CACHE_HEADER: &str = "# signature cache v1";
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01B3;
+
+/// Where a variant of a signature db entry was found.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CachedMatch {
+    pub module: String,
+    /// The [`module_checksum`] of the module the match was found in.
+    pub checksum: u64,
+    /// The index of the variant that matched.
+    pub variant: usize,
+    /// The match, relative to the base of the module.
+    pub rva: usize,
+}
+
+/// Cached matches by the name of their signature db entry.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SignatureCache {
+    entries: BTreeMap<String, CachedMatch>,
+}
+
+impl SignatureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a cache written by `to_string`.
+    ///
+    /// Every line holds the name, the module, the checksum, the variant and the RVA,
+    /// separated by tabs. A cache without the header of this version is dropped, the
+    /// returned one is empty.
+    /// # Examples
+    /// ```
+    /// use sdk::utils::signature_cache::SignatureCache;
+    ///
+    /// let cache = SignatureCache::parse(
+    ///     "# signature cache v1\nIInput\tclient\t0x1F2E3D4C5B6A7988\t0\t0x2A1B4C",
+    /// )?;
+    /// let input = cache.get("IInput").unwrap();
+    ///
+    /// assert_eq!((input.variant, input.rva), (0, 0x2A1B4C));
+    /// assert_eq!(SignatureCache::parse(&cache.to_string())?, cache);
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut entries = BTreeMap::new();
+
+        if text.lines().next().map(|line| line.trim_end_matches('\r')) != Some(CACHE_HEADER) {
+            return Ok(Self::default());
+        }
+
+        for (number, line) in text.lines().enumerate().skip(1) {
+            let line = line.trim_end_matches('\r');
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || Error::Other {
+                msg: format!("invalid signature cache line {}: {}", number + 1, line),
+            };
+
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            if fields.len() != 5 {
+                return Err(invalid());
+            }
+
+            entries.insert(
+                fields[0].to_string(),
+                CachedMatch {
+                    module: fields[1].to_string(),
+                    checksum: fields[2]
+                        .strip_prefix("0x")
+                        .and_then(|checksum| u64::from_str_radix(checksum, 16).ok())
+                        .ok_or_else(invalid)?,
+                    variant: fields[3].parse().map_err(|_| invalid())?,
+                    rva: fields[4]
+                        .strip_prefix("0x")
+                        .and_then(|rva| usize::from_str_radix(rva, 16).ok())
+                        .ok_or_else(invalid)?,
+                },
+            );
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Reads a cache from `path`.
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Writes the cache to `path`.
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.to_string()).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CachedMatch> {
+        self.entries.get(name)
+    }
+
+    /// Stores `cached` for the entry `name`, replacing what was stored before.
+    pub fn insert(&mut self, name: impl Into<String>, cached: CachedMatch) {
+        self.entries.insert(name.into(), cached);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<CachedMatch> {
+        self.entries.remove(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for SignatureCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", CACHE_HEADER)?;
+
+        for (name, cached) in &self.entries {
+            writeln!(
+                f,
+                "{}\t{}\t0x{:016X}\t{}\t0x{:X}",
+                name, cached.module, cached.checksum, cached.variant, cached.rva
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Hashes the executable sections of the given module, `None` if it has none.
+///
+/// The checksum changes whenever the code of the module does, e.g. after a game update,
+/// but not with the address the module was loaded at.
+pub fn module_checksum(module_name: *const c_char) -> Option<u64> {
+    checksum(
+        &module_sections(module_name),
+        &module_relocations(module_name),
+    )
+}
+
+/// Hashes the executable ones of `sections`, see [`module_checksum`].
+pub(crate) fn checksum(sections: &[Section], relocations: &[(usize, usize)]) -> Option<u64> {
+    let mut sections = sections
+        .iter()
+        .filter(|section| section.executable)
+        .collect::<Vec<_>>();
+
+    if sections.is_empty() {
+        return None;
+    }
+
+    sections.sort_by_key(|section| section.address);
+
+    // FNV-1a, but over whole words, the sections are large and read on every start.
+    let mut hash = FNV_OFFSET;
+    let mut mix = |word: u64| hash = (hash ^ word).wrapping_mul(FNV_PRIME);
+
+    for section in sections {
+        let mut bytes = Cow::Borrowed(unsafe { section.bytes() });
+
+        // Relocated values depend on where the module was loaded, they are hashed as zeroes.
+        for (address, size) in relocations {
+            if *address >= section.address && address + size <= section.address + section.size {
+                let offset = address - section.address;
+                bytes.to_mut()[offset..offset + size].fill(0);
+            }
+        }
+
+        let mut words = bytes.chunks_exact(8);
+
+        mix(bytes.len() as u64);
+
+        for word in &mut words {
+            let mut buffer = [0; 8];
+            buffer.copy_from_slice(word);
+            mix(u64::from_le_bytes(buffer));
+        }
+
+        for byte in words.remainder() {
+            mix(*byte as u64);
+        }
+    }
+
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(bytes: &[u8], executable: bool) -> Section {
+        Section {
+            name: ".text".into(),
+            address: bytes.as_ptr() as usize,
+            size: bytes.len(),
+            executable,
+            writable: false,
+        }
+    }
+
+    #[test]
+    fn relocations_are_zeroed() {
+        // mov ecx, offset g_Input; call [eax + 0x38]; at two different load addresses.
+        let first = [
+            0xB9u8, 0x00, 0x10, 0x40, 0x10, 0xFF, 0x50, 0x38, 0x90, 0x90, 0xC3,
+        ];
+        let mut second = first;
+        second[1..5].copy_from_slice(&[0x00, 0x10, 0x40, 0x60]);
+
+        let first_relocations = [(first.as_ptr() as usize + 1, 4)];
+        let second_relocations = [(second.as_ptr() as usize + 1, 4)];
+
+        let expected = checksum(&[section(&first, true)], &first_relocations);
+
+        assert!(expected.is_some());
+        assert_eq!(
+            expected,
+            checksum(&[section(&second, true)], &second_relocations)
+        );
+        assert_ne!(expected, checksum(&[section(&second, true)], &[]));
+
+        // Bytes that are not relocated still count.
+        second[6] = 0x3C;
+        assert_ne!(
+            expected,
+            checksum(&[section(&second, true)], &second_relocations)
+        );
+    }
+
+    #[test]
+    fn other_versions_are_dropped() {
+        let line = "IInput\tclient\t0x1F2E3D4C5B6A7988\t0\t0x2A1B4C";
+
+        assert!(
+            SignatureCache::parse(&format!("{}\n{}", CACHE_HEADER, line))
+                .unwrap()
+                .get("IInput")
+                .is_some()
+        );
+        assert_eq!(SignatureCache::parse(line).unwrap(), SignatureCache::new());
+        assert_eq!(
+            SignatureCache::parse(&format!("# signature cache v2\n{}", line)).unwrap(),
+            SignatureCache::new()
+        );
+    }
+
+    #[test]
+    fn executable_sections_only() {
+        let code = [0x90u8, 0xC3];
+        let data = [0x01u8, 0x02, 0x03];
+
+        assert_eq!(
+            checksum(&[section(&code, true), section(&data, false)], &[]),
+            checksum(&[section(&code, true)], &[])
+        );
+        assert_eq!(checksum(&[section(&data, false)], &[]), None);
+    }
+}
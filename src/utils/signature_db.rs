@@ -0,0 +1,584 @@
+//! Signatures loaded from a text file instead of being compiled in.
+//!
+//! Every line of the file holds the name, the module, the game builds, the resolution
+//! chain and the signature of a variant, separated by tabs. Lines with the same name are
+//! variants of one entry, they are tried in order and the first one that resolves wins.
+//! The signatures the SDK uses itself are bundled (see [`SignatureDb::bundled`]).
+//! # Examples
+//! ```no_run
+//! use sdk::utils::signature_db::{self, SignatureDb};
+//!
+//! let mut db = SignatureDb::bundled();
+//! db.merge(SignatureDb::load("signatures.txt")?);
+//!
+//! let found = db.resolve("IInput", Some(1575))?;
+//!
+//! println!("IInput is at 0x{:X}, found by variant {}", found.address, found.variant + 1);
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use libc::c_char;
+use static_init::dynamic;
+
+use crate::utils::batch::SignatureSet;
+use crate::utils::error::Error;
+use crate::utils::memory::{module_base, module_relocations, module_sections, Section, Sections};
+use crate::utils::platform::module_by_name;
+use crate::utils::resolve::{Chain, Pattern};
+use crate::utils::signature::Signature;
+use crate::utils::signature_cache::{checksum, CachedMatch, SignatureCache};
+
+const DB_HEADER: &str = "# signature db v1";
+const BUNDLED: &str = include_str!("signatures.txt");
+
+#[dynamic]
+static mut DATABASE: SignatureDb = SignatureDb::bundled();
+#[dynamic]
+static mut BUILD: Option<u32> = None;
+#[dynamic]
+static mut CACHE: Option<SignatureCache> = None;
+
+/// Game builds a variant applies to, both ends are inclusive and `None` is unbounded.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BuildRange {
+    pub first: Option<u32>,
+    pub last: Option<u32>,
+}
+
+impl BuildRange {
+    /// Every build.
+    pub const ALL: Self = Self {
+        first: None,
+        last: None,
+    };
+
+    /// Returns `true` if `build` is in the range, an unknown build is in every range.
+    pub fn contains(&self, build: Option<u32>) -> bool {
+        match build {
+            Some(build) => {
+                self.first.is_none_or(|first| build >= first)
+                    && self.last.is_none_or(|last| build <= last)
+            }
+            None => true,
+        }
+    }
+
+    /// Parses `*`, `1500`, `1500-`, `-1575` or `1500-1575`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let bound = |text: &str| match text {
+            "" => Some(None),
+            text => text.parse().ok().map(Some),
+        };
+
+        let range = match text.split_once('-') {
+            _ if text == "*" => Self::ALL,
+            Some((first, last)) => Self {
+                first: bound(first)?,
+                last: bound(last)?,
+            },
+            None => {
+                let build = Some(text.parse().ok()?);
+
+                Self {
+                    first: build,
+                    last: build,
+                }
+            }
+        };
+
+        Some(range)
+    }
+}
+
+impl fmt::Display for BuildRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.first, self.last) {
+            (None, None) => write!(f, "*"),
+            (Some(first), Some(last)) if first == last => write!(f, "{}", first),
+            (first, last) => {
+                if let Some(first) = first {
+                    write!(f, "{}", first)?;
+                }
+
+                write!(f, "-")?;
+
+                if let Some(last) = last {
+                    write!(f, "{}", last)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// One alternative signature of an [`Entry`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Variant {
+    pub pattern: Pattern,
+    pub builds: BuildRange,
+}
+
+/// Everything known about how to find one address.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub name: String,
+    /// The module the signatures are in, see [`module_by_name`].
+    pub module: String,
+    /// Variants in the order they are tried.
+    pub variants: Vec<Variant>,
+}
+
+impl Entry {
+    /// Iterates over the variants that apply to `build` and their indices.
+    pub fn variants_for(&self, build: Option<u32>) -> impl Iterator<Item = (usize, &Variant)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .filter(move |(_, variant)| variant.builds.contains(build))
+    }
+
+    /// Resolves the first variant `found` returns a match for.
+    fn pick(
+        &self,
+        build: Option<u32>,
+        found: impl Fn(usize) -> Option<usize>,
+    ) -> Result<Match, Error> {
+        let mut failure = None;
+        let mut applicable = false;
+
+        for (index, variant) in self.variants_for(build) {
+            applicable = true;
+
+            let address = match found(index) {
+                Some(address) => address,
+                None => continue,
+            };
+
+            match unsafe { variant.pattern.resolve(&self.name, address) } {
+                Ok(address) => {
+                    return Ok(Match {
+                        variant: index,
+                        address,
+                        cached: false,
+                    })
+                }
+                Err(error) => {
+                    failure.get_or_insert(error);
+                }
+            }
+        }
+
+        Err(match failure {
+            Some(error) => error,
+            None if !applicable => Error::Other {
+                msg: format!(
+                    "no variant of {} applies to build {}",
+                    self.name,
+                    build.unwrap_or_default()
+                ),
+            },
+            None => Error::NotFound {
+                item: self.name.clone(),
+            },
+        })
+    }
+}
+
+/// Which variant of an [`Entry`] matched and what its chain resolved to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Match {
+    /// The index into [`Entry::variants`].
+    pub variant: usize,
+    pub address: usize,
+    /// `true` if the match came from a [`SignatureCache`] instead of a scan.
+    pub cached: bool,
+}
+
+/// The outcome of [`SignatureDb::resolve_all`] for every entry, by name.
+#[derive(Clone, Debug, Default)]
+pub struct Resolutions {
+    pub results: BTreeMap<String, Result<Match, Error>>,
+}
+
+impl Resolutions {
+    /// Returns the outcome for `name`, `Error::NotFound` if there is no such entry.
+    pub fn get(&self, name: &str) -> Result<Match, Error> {
+        self.results.get(name).cloned().unwrap_or_else(|| {
+            Err(Error::NotFound {
+                item: name.to_string(),
+            })
+        })
+    }
+}
+
+/// Named entries, each with one or more signature variants.
+#[derive(Clone, Debug, Default)]
+pub struct SignatureDb {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl SignatureDb {
+    /// The signatures the SDK is shipped with.
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("the bundled signature db is invalid")
+    }
+
+    /// Parses a database written by `to_string`.
+    ///
+    /// Fails if the text doesn't start with the header of this version.
+    /// # Examples
+    /// ```
+    /// use sdk::utils::signature_db::SignatureDb;
+    ///
+    /// let db = SignatureDb::parse(
+    ///     "# signature db v1\n\
+    ///      IInput\tclient\t-1499\toffset 0x1, deref\tB9 ? ? ? ? 8B 40 38\n\
+    ///      IInput\tclient\t1500-\toffset 0x1, deref\tB9 ? ? ? ? 8B 40 3C",
+    /// )?;
+    ///
+    /// let input = db.get("IInput").unwrap();
+    ///
+    /// assert_eq!(input.variants.len(), 2);
+    /// assert_eq!(input.variants_for(Some(1575)).next().unwrap().0, 1);
+    /// assert_eq!(SignatureDb::parse(&db.to_string())?.get("IInput"), Some(input));
+    /// assert!(SignatureDb::parse("IInput\tclient\t-\toffset 0x1\tB9").is_err());
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut db = Self::default();
+
+        match text.lines().next().map(|line| line.trim_end_matches('\r')) {
+            Some(DB_HEADER) => {}
+            Some(header) if header.starts_with("# signature db") => {
+                return Err(Error::Other {
+                    msg: format!("unsupported signature db version: {}", header),
+                })
+            }
+            _ => {
+                return Err(Error::Other {
+                    msg: format!("signature db doesn't start with \"{}\"", DB_HEADER),
+                })
+            }
+        }
+
+        for (number, line) in text.lines().enumerate().skip(1) {
+            let line = line.trim_end_matches('\r');
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |reason: String| Error::Other {
+                msg: format!(
+                    "invalid signature db line {}: {} ({})",
+                    number + 1,
+                    line,
+                    reason
+                ),
+            };
+
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            if fields.len() != 5 {
+                return Err(invalid(format!("{} fields instead of 5", fields.len())));
+            }
+
+            if module_by_name(fields[1]).is_none() {
+                return Err(invalid(format!("unknown module {}", fields[1])));
+            }
+
+            let variant = Variant {
+                builds: BuildRange::parse(fields[2])
+                    .ok_or_else(|| invalid(format!("invalid builds {}", fields[2])))?,
+                pattern: Pattern {
+                    chain: Chain::parse(fields[3]).map_err(|error| invalid(error.to_string()))?,
+                    signature: Signature::parse(fields[4])
+                        .map_err(|error| invalid(error.to_string()))?,
+                },
+            };
+
+            let entry = db
+                .entries
+                .entry(fields[0].to_string())
+                .or_insert_with(|| Entry {
+                    name: fields[0].to_string(),
+                    module: fields[1].to_string(),
+                    variants: Vec::new(),
+                });
+
+            if entry.module != fields[1] {
+                return Err(invalid(format!("{} is in {}", entry.name, entry.module)));
+            }
+
+            entry.variants.push(variant);
+        }
+
+        Ok(db)
+    }
+
+    /// Reads a database from `path`.
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Writes the database to `path`.
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.to_string()).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.entries.get(name)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.values()
+    }
+
+    /// Adds `entry`, returning the entry with the same name it replaced.
+    pub fn insert(&mut self, entry: Entry) -> Option<Entry> {
+        self.entries.insert(entry.name.clone(), entry)
+    }
+
+    /// Adds every entry of `other`, replacing entries with the same name.
+    pub fn merge(&mut self, other: SignatureDb) {
+        self.entries.extend(other.entries);
+    }
+
+    /// Finds the entry `name` for the game build `build` in its module.
+    pub fn resolve(&self, name: &str, build: Option<u32>) -> Result<Match, Error> {
+        self.resolve_entries(&[self.entry(name)?], build, None)
+            .get(name)
+    }
+
+    /// Same as [`SignatureDb::resolve`], but skips the scan if `cache` has a valid match
+    /// and stores the match otherwise.
+    pub fn resolve_cached(
+        &self,
+        name: &str,
+        build: Option<u32>,
+        cache: &mut SignatureCache,
+    ) -> Result<Match, Error> {
+        self.resolve_entries(&[self.entry(name)?], build, Some(cache))
+            .get(name)
+    }
+
+    /// Finds every entry for the game build `build`, scanning each module once.
+    pub fn resolve_all(&self, build: Option<u32>) -> Resolutions {
+        self.resolve_entries(&self.entries().collect::<Vec<_>>(), build, None)
+    }
+
+    /// Same as [`SignatureDb::resolve_all`], but only scans for the entries `cache` has no
+    /// valid match for and stores their matches.
+    pub fn resolve_all_cached(
+        &self,
+        build: Option<u32>,
+        cache: &mut SignatureCache,
+    ) -> Resolutions {
+        self.resolve_entries(&self.entries().collect::<Vec<_>>(), build, Some(cache))
+    }
+
+    fn entry(&self, name: &str) -> Result<&Entry, Error> {
+        self.get(name).ok_or_else(|| Error::NotFound {
+            item: name.to_string(),
+        })
+    }
+
+    fn resolve_entries(
+        &self,
+        entries: &[&Entry],
+        build: Option<u32>,
+        mut cache: Option<&mut SignatureCache>,
+    ) -> Resolutions {
+        let key = |name: &str, variant: usize| format!("{}\t{}", name, variant);
+        let mut results = BTreeMap::new();
+        let mut modules: BTreeMap<&str, LoadedModule> = BTreeMap::new();
+        let mut sets: BTreeMap<&str, SignatureSet> = BTreeMap::new();
+        let mut pending = Vec::new();
+
+        for entry in entries {
+            let module = match module_by_name(&entry.module) {
+                Some(module) => module,
+                None => {
+                    results.insert(
+                        entry.name.clone(),
+                        Err(Error::Other {
+                            msg: format!(
+                                "{} is in the unknown module {}",
+                                entry.name, entry.module
+                            ),
+                        }),
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(cache) = cache.as_deref() {
+                let loaded = modules
+                    .entry(&entry.module)
+                    .or_insert_with(|| LoadedModule::new(module));
+
+                if let Some(cached) = cache.get(&entry.name) {
+                    let resolved = loaded.verify(entry, cached, build).and_then(|address| {
+                        entry
+                            .pick(build, |index| (index == cached.variant).then_some(address))
+                            .ok()
+                    });
+
+                    if let Some(resolved) = resolved {
+                        results.insert(
+                            entry.name.clone(),
+                            Ok(Match {
+                                cached: true,
+                                ..resolved
+                            }),
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            let set = sets.entry(&entry.module).or_default();
+
+            for (index, variant) in entry.variants_for(build) {
+                set.add(key(&entry.name, index), variant.pattern.signature.clone());
+            }
+
+            pending.push(*entry);
+        }
+
+        let mut found = BTreeMap::new();
+
+        for (module, set) in &sets {
+            if let Some(module) = module_by_name(module) {
+                found.extend(set.scan_module(module, Sections::Executable).found);
+            }
+        }
+
+        for entry in pending {
+            let result = entry.pick(build, |index| found.get(&key(&entry.name, index)).copied());
+
+            if let Some(cache) = cache.as_deref_mut() {
+                let loaded = &modules[entry.module.as_str()];
+
+                match (&result, loaded.base, loaded.checksum) {
+                    (Ok(resolved), Some(base), Some(checksum)) => cache.insert(
+                        entry.name.clone(),
+                        CachedMatch {
+                            module: entry.module.clone(),
+                            checksum,
+                            variant: resolved.variant,
+                            rva: found[&key(&entry.name, resolved.variant)] - base,
+                        },
+                    ),
+                    _ => {
+                        cache.remove(&entry.name);
+                    }
+                }
+            }
+
+            results.insert(entry.name.clone(), result);
+        }
+
+        Resolutions { results }
+    }
+}
+
+/// What verifying cached matches needs to know about a module, read once per resolve.
+struct LoadedModule {
+    base: Option<usize>,
+    checksum: Option<u64>,
+    sections: Vec<Section>,
+}
+
+impl LoadedModule {
+    fn new(module: *const c_char) -> Self {
+        let sections = module_sections(module);
+
+        Self {
+            base: module_base(module),
+            checksum: checksum(&sections, &module_relocations(module)),
+            sections,
+        }
+    }
+
+    /// Returns the address of `cached` if the module is unchanged and the signature of
+    /// its variant still matches there.
+    fn verify(&self, entry: &Entry, cached: &CachedMatch, build: Option<u32>) -> Option<usize> {
+        if cached.module != entry.module || Some(cached.checksum) != self.checksum {
+            return None;
+        }
+
+        let (_, variant) = entry
+            .variants_for(build)
+            .find(|(index, _)| *index == cached.variant)?;
+        let address = self.base?.checked_add(cached.rva)?;
+        let section = self.sections.iter().find(|section| {
+            section.executable
+                && address >= section.address
+                && address < section.address + section.size
+        })?;
+
+        let bytes = unsafe { section.bytes() };
+
+        variant
+            .pattern
+            .signature
+            .matches(&bytes[address - section.address..])
+            .then_some(address)
+    }
+}
+
+impl fmt::Display for SignatureDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", DB_HEADER)?;
+
+        for entry in self.entries() {
+            for variant in &entry.variants {
+                writeln!(
+                    f,
+                    "{}\t{}\t{}\t{}\t{}",
+                    entry.name,
+                    entry.module,
+                    variant.builds,
+                    variant.pattern.chain,
+                    variant.pattern.signature
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the database, game build and cache used by [`resolve`], `sdk::initialize`
+/// installs the ones from its `LoadOptions`.
+pub fn install(db: SignatureDb, build: Option<u32>, cache: Option<SignatureCache>) {
+    *DATABASE.write() = db;
+    *BUILD.write() = build;
+    *CACHE.write() = cache;
+}
+
+/// Finds the entry `name` of the installed database.
+pub fn resolve(name: &str) -> Result<Match, Error> {
+    let db = DATABASE.read();
+    let build = *BUILD.read();
+
+    match CACHE.write().as_mut() {
+        Some(cache) => db.resolve_cached(name, build, cache),
+        None => db.resolve(name, build),
+    }
+}
@@ -0,0 +1,376 @@
+//! Generating the shortest unique signature for an address.
+//!
+//! Instructions are added one at a time from the address until the signature only matches
+//! once in the executable sections, then it is cut down to the shortest length that is still
+//! unique. Operands that change when the module is rebuilt or loaded somewhere else are
+//! wildcards: relative branch targets, `rip` relative displacements and relocated values.
+//! # Examples
+//! ```no_run
+//! use sdk::utils::image::Image;
+//! use sdk::utils::signature_gen::Code;
+//!
+//! let file = std::fs::read("client.dll").unwrap();
+//! let image = Image::parse(&file)?;
+//!
+//! println!("{}", Code::from_image(&image).generate(0x3B2C10)?);
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use libc::c_char;
+
+use crate::utils::error::Error;
+use crate::utils::image::Image;
+use crate::utils::memory::{module_relocations, module_sections};
+use crate::utils::signature::Signature;
+use crate::utils::x86::{decode, Instruction, Mode};
+
+/// Signatures longer than this are not worth it, the address is in code that repeats.
+const MAX_SIGNATURE_LENGTH: usize = 128;
+
+/// The code of a module and its read-only data, from a file or from memory.
+pub struct Code<'a> {
+    pub(crate) mode: Mode,
+    /// The executable sections and their addresses.
+    pub(crate) sections: Vec<(usize, &'a [u8])>,
+    /// The sections that are neither executable nor writable, e.g. `.rdata`.
+    pub(crate) data: Vec<(usize, &'a [u8])>,
+    /// The address and size of every relocated value, sorted by address.
+    relocations: Vec<(usize, usize)>,
+    /// What has to be added to an address to get the value absolute operands hold for it.
+    pub(crate) image_base: usize,
+    /// The addresses the module's code and data can be referenced by.
+    references: Range<usize>,
+}
+
+impl<'a> Code<'a> {
+    /// The code of a module file, addresses are RVAs.
+    pub fn from_image(image: &Image<'a>) -> Self {
+        Self::new(
+            image.mode,
+            image
+                .sections
+                .iter()
+                .map(|section| {
+                    (
+                        section.rva,
+                        section.size,
+                        section.executable,
+                        section.writable,
+                        section.data,
+                    )
+                })
+                .collect(),
+            image.relocations.clone(),
+            image.image_base,
+        )
+    }
+
+    /// The code of a loaded module, addresses are absolute.
+    pub fn from_module(module_name: *const c_char) -> Code<'static> {
+        Code::new(
+            if cfg!(target_pointer_width = "64") {
+                Mode::X64
+            } else {
+                Mode::X86
+            },
+            module_sections(module_name)
+                .iter()
+                .map(|section| {
+                    let bytes = unsafe { section.bytes() };
+                    (
+                        section.address,
+                        section.size,
+                        section.executable,
+                        section.writable,
+                        bytes,
+                    )
+                })
+                .collect(),
+            module_relocations(module_name),
+            0,
+        )
+    }
+
+    /// Takes the address, size, whether it is executable and writable and the contents of
+    /// every section.
+    fn new(
+        mode: Mode,
+        sections: Vec<(usize, usize, bool, bool, &'a [u8])>,
+        mut relocations: Vec<(usize, usize)>,
+        image_base: usize,
+    ) -> Self {
+        relocations.sort_unstable();
+
+        let start = sections.iter().map(|section| section.0).min().unwrap_or(0);
+        let end = sections
+            .iter()
+            .map(|section| section.0.saturating_add(section.1))
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            mode,
+            sections: sections
+                .iter()
+                .filter(|section| section.2)
+                .map(|section| (section.0, section.4))
+                .collect(),
+            data: sections
+                .iter()
+                .filter(|section| !section.2 && !section.3)
+                .map(|section| (section.0, section.4))
+                .collect(),
+            relocations,
+            image_base,
+            references: image_base.saturating_add(start)..image_base.saturating_add(end),
+        }
+    }
+
+    /// Returns the shortest signature that starts at `address` and matches nowhere else.
+    ///
+    /// `address` has to be the start of an instruction in an executable section.
+    pub fn generate(&self, address: usize) -> Result<Signature, Error> {
+        let (start, code) = self
+            .sections
+            .iter()
+            .find(|(start, code)| address >= *start && address < start + code.len())
+            .ok_or_else(|| Error::NotFound {
+                item: format!("0x{:X} in the executable sections", address),
+            })?;
+
+        let code = &code[address - start..];
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+
+        while bytes.len() < MAX_SIGNATURE_LENGTH {
+            let offset = bytes.len();
+            let instruction = decode(&code[offset..], self.mode).ok_or_else(|| Error::Other {
+                msg: format!("can't decode the instruction at 0x{:X}", address + offset),
+            })?;
+
+            bytes.extend_from_slice(&code[offset..offset + instruction.length]);
+            mask.extend(self.instruction_mask(address + offset, &code[offset..], &instruction));
+
+            if !mask.contains(&true) || !self.is_unique(&bytes, &mask) {
+                continue;
+            }
+
+            // Every byte can only remove matches, so the shortest unique length is at most
+            // one instruction shorter.
+            let length = (offset + 1..bytes.len())
+                .find(|length| {
+                    mask[..*length].contains(&true)
+                        && self.is_unique(&bytes[..*length], &mask[..*length])
+                })
+                .unwrap_or(bytes.len());
+
+            // Trailing wildcards don't narrow down anything.
+            let length = mask[..length]
+                .iter()
+                .rposition(|concrete| *concrete)
+                .unwrap_or(0)
+                + 1;
+
+            bytes.truncate(length);
+            mask.truncate(length);
+
+            return Signature::new(bytes, mask);
+        }
+
+        Err(Error::Other {
+            msg: format!(
+                "no unique signature for 0x{:X} within {} bytes",
+                address, MAX_SIGNATURE_LENGTH
+            ),
+        })
+    }
+
+    /// Returns `true` for every byte of `instruction` that stays the same between builds.
+    fn instruction_mask(
+        &self,
+        address: usize,
+        code: &[u8],
+        instruction: &Instruction,
+    ) -> Vec<bool> {
+        let mut mask = vec![true; instruction.length];
+        let mut wildcard = |offset: usize, size: usize| {
+            mask[offset..offset + size]
+                .iter_mut()
+                .for_each(|concrete| *concrete = false)
+        };
+
+        if let Some(relative) = instruction.relative.filter(|relative| relative.size >= 4) {
+            wildcard(relative.offset, relative.size);
+        }
+
+        for operand in [instruction.displacement, instruction.immediate]
+            .iter()
+            .flatten()
+        {
+            let start = address + operand.offset;
+            let first = self
+                .relocations
+                .partition_point(|(relocation, size)| relocation + size <= start);
+            let relocated = self.relocations[first..]
+                .first()
+                .is_some_and(|(relocation, _)| *relocation < start + operand.size);
+
+            // Without relocations, 32 bit values that point into the module are most likely
+            // addresses as well.
+            let reference = self.mode == Mode::X86 && operand.size == 4 && {
+                let mut value = [0; 4];
+                value.copy_from_slice(&code[operand.offset..operand.offset + 4]);
+
+                self.references
+                    .contains(&(u32::from_le_bytes(value) as usize))
+            };
+
+            let displacement = Some(*operand) == instruction.displacement;
+            let rip_relative = instruction.rip_relative && displacement;
+            let absolute = self.mode == Mode::X86 && instruction.absolute && displacement;
+
+            if relocated || reference || rip_relative || absolute {
+                wildcard(operand.offset, operand.size);
+            }
+        }
+
+        mask
+    }
+
+    fn is_unique(&self, bytes: &[u8], mask: &[bool]) -> bool {
+        let signature = match Signature::new(bytes.to_vec(), mask.to_vec()) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+
+        let second = self
+            .sections
+            .iter()
+            .flat_map(|(_, code)| signature.find_iter(code))
+            .nth(1);
+
+        second.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::utils::image::{Format, ImageSection};
+
+    fn image<'a>(
+        mode: Mode,
+        image_base: usize,
+        code: &'a [u8],
+        relocations: &[usize],
+    ) -> Image<'a> {
+        Image {
+            format: Format::Pe,
+            mode,
+            image_base,
+            sections: vec![
+                ImageSection {
+                    name: ".text".into(),
+                    rva: 0x1000,
+                    size: code.len(),
+                    executable: true,
+                    writable: false,
+                    data: code,
+                },
+                ImageSection {
+                    name: ".data".into(),
+                    rva: 0x3000,
+                    size: 0x100,
+                    executable: false,
+                    writable: true,
+                    data: &[],
+                },
+            ],
+            relocations: relocations.iter().map(|rva| (*rva, 4)).collect(),
+        }
+    }
+
+    /// Writes `instructions` to `code` at `offset`.
+    fn place(code: &mut [u8], offset: usize, instructions: &[u8]) {
+        code[offset..offset + instructions.len()].copy_from_slice(instructions);
+    }
+
+    #[test]
+    fn x86_wildcards() {
+        let mut code = [0xCC; 0x40];
+
+        // push 5; mov ecx, relocated; push data; call rel32; mov eax, [eax + 0x38]; ret
+        place(
+            &mut code,
+            0x00,
+            &[
+                0x6A, 0x05, 0xB9, 0x78, 0x56, 0x34, 0x12, 0x68, 0x00, 0x30, 0x00, 0x10, 0xE8, 0x10,
+                0x00, 0x00, 0x00, 0x8B, 0x40, 0x38, 0xC3,
+            ],
+        );
+        // The same function, only reading [eax + 0x3C].
+        place(
+            &mut code,
+            0x20,
+            &[
+                0x6A, 0x05, 0xB9, 0x00, 0x00, 0x00, 0x00, 0x68, 0x10, 0x30, 0x00, 0x10, 0xE8, 0x20,
+                0x00, 0x00, 0x00, 0x8B, 0x40, 0x3C, 0xC3,
+            ],
+        );
+
+        let image = image(Mode::X86, 0x1000_0000, &code, &[0x1003, 0x1023]);
+        let code = Code::from_image(&image);
+
+        assert_eq!(
+            code.generate(0x1000).unwrap().to_string(),
+            "6A 05 B9 ? ? ? ? 68 ? ? ? ? E8 ? ? ? ? 8B 40 38"
+        );
+        assert_eq!(
+            code.generate(0x1020).unwrap().to_string(),
+            "6A 05 B9 ? ? ? ? 68 ? ? ? ? E8 ? ? ? ? 8B 40 3C"
+        );
+        assert!(code.generate(0x3000).is_err());
+    }
+
+    #[test]
+    fn x64_wildcards() {
+        let mut code = [0xCC; 0x20];
+
+        // mov rax, [rip + 0x10]; call [rax + 0x38]; ret
+        place(
+            &mut code,
+            0x00,
+            &[
+                0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00, 0xFF, 0x50, 0x38, 0xC3,
+            ],
+        );
+        place(
+            &mut code,
+            0x10,
+            &[
+                0x48, 0x8B, 0x05, 0x20, 0x00, 0x00, 0x00, 0xFF, 0x50, 0x40, 0xC3,
+            ],
+        );
+
+        let unique = image(Mode::X64, 0x1_4000_0000, &code, &[]);
+
+        assert_eq!(
+            Code::from_image(&unique)
+                .generate(0x1000)
+                .unwrap()
+                .to_string(),
+            "48 8B 05 ? ? ? ? FF 50 38"
+        );
+
+        // Code that repeats for longer than a signature can be has no unique signature.
+        let code = code[..0x10].repeat(0x10);
+        let repeated = image(Mode::X64, 0x1_4000_0000, &code, &[]);
+
+        assert!(Code::from_image(&repeated).generate(0x1000).is_err());
+    }
+}
@@ -0,0 +1,464 @@
+//! A length decoder for x86 and x86-64 instructions.
+//!
+//! It only finds out how long an instruction is and where its displacement and immediate
+//! are, which is what generating signatures needs to wildcard addresses that change
+//! between builds.
+//! # Examples
+//! ```
+//! use sdk::utils::x86::{decode, Mode};
+//!
+//! // call rel32
+//! let call = decode(&[0xE8, 0x10, 0x20, 0x30, 0x40], Mode::X86).unwrap();
+//!
+//! assert_eq!(call.length, 5);
+//! assert_eq!(call.relative.unwrap().offset, 1);
+//!
+//! // mov rax, [rip + disp32]
+//! let mov = decode(&[0x48, 0x8B, 0x05, 0x10, 0x20, 0x30, 0x40], Mode::X64).unwrap();
+//!
+//! assert!(mov.rip_relative);
+//! assert_eq!(mov.displacement.unwrap().offset, 3);
+//! ```
+
+/// The size of addresses and the default size of operands.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Mode {
+    X86,
+    X64,
+}
+
+/// Where an operand is, relative to the start of its instruction.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Operand {
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// The layout of a decoded instruction.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Instruction {
+    pub length: usize,
+    /// The displacement of a memory operand.
+    pub displacement: Option<Operand>,
+    /// `true` if the displacement is relative to the next instruction (`[rip + disp32]`).
+    pub rip_relative: bool,
+    /// `true` if the displacement is an absolute address (`[disp32]`).
+    pub absolute: bool,
+    /// The immediate, for `enter` only the first one.
+    pub immediate: Option<Operand>,
+    /// The target of a relative branch, relative to the next instruction.
+    pub relative: Option<Operand>,
+}
+
+/// Opcodes of the one byte map that have a ModRM byte, one bit per opcode.
+const MODRM_ONE_BYTE: [u32; 8] = bitmap(&[
+    0x00, 0x01, 0x02, 0x03, 0x08, 0x09, 0x0A, 0x0B, 0x10, 0x11, 0x12, 0x13, 0x18, 0x19, 0x1A, 0x1B,
+    0x20, 0x21, 0x22, 0x23, 0x28, 0x29, 0x2A, 0x2B, 0x30, 0x31, 0x32, 0x33, 0x38, 0x39, 0x3A, 0x3B,
+    0x62, 0x63, 0x69, 0x6B, 0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B,
+    0x8C, 0x8D, 0x8E, 0x8F, 0xC0, 0xC1, 0xC4, 0xC5, 0xC6, 0xC7, 0xD0, 0xD1, 0xD2, 0xD3, 0xD8, 0xD9,
+    0xDA, 0xDB, 0xDC, 0xDD, 0xDE, 0xDF, 0xF6, 0xF7, 0xFE, 0xFF,
+]);
+
+/// Opcodes of the `0F` map that have no ModRM byte.
+const NO_MODRM_TWO_BYTE: [u32; 8] = bitmap(&[
+    0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0E, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37,
+    0x77, 0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x8D, 0x8E,
+    0x8F, 0xA0, 0xA1, 0xA2, 0xA8, 0xA9, 0xAA, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD, 0xCE, 0xCF,
+]);
+
+/// Opcodes of the `0F` map that have an 8 bit immediate.
+const IMM8_TWO_BYTE: [u32; 8] = bitmap(&[
+    0x0F, 0x70, 0x71, 0x72, 0x73, 0xA4, 0xAC, 0xBA, 0xC2, 0xC4, 0xC5, 0xC6,
+]);
+
+const fn bitmap(opcodes: &[u8]) -> [u32; 8] {
+    let mut bits = [0; 8];
+    let mut i = 0;
+
+    while i < opcodes.len() {
+        bits[opcodes[i] as usize / 32] |= 1 << (opcodes[i] % 32);
+        i += 1;
+    }
+
+    bits
+}
+
+fn contains(bits: &[u32; 8], opcode: u8) -> bool {
+    bits[opcode as usize / 32] & 1 << (opcode % 32) != 0
+}
+
+/// Which opcode map an opcode is from.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Map {
+    OneByte,
+    TwoByte,
+    ThreeByte38,
+    ThreeByte3A,
+}
+
+/// Decodes the instruction at the start of `code`.
+///
+/// Returns `None` if `code` ends before the instruction does or starts with more than 14
+/// prefixes.
+pub fn decode(code: &[u8], mode: Mode) -> Option<Instruction> {
+    let mut position = 0;
+    let mut operand_size_override = false;
+    let mut address_size_override = false;
+    let mut rex_w = false;
+
+    loop {
+        match *code.get(position)? {
+            0x66 => operand_size_override = true,
+            0x67 => address_size_override = true,
+            0xF0 | 0xF2 | 0xF3 | 0x2E | 0x36 | 0x3E | 0x26 | 0x64 | 0x65 => {}
+            _ => break,
+        }
+
+        position += 1;
+
+        if position >= 15 {
+            return None;
+        }
+    }
+
+    if mode == Mode::X64 && code[position] & 0xF0 == 0x40 {
+        rex_w = code[position] & 0x08 != 0;
+        position += 1;
+    }
+
+    // `imm16/32`, 16 bits with an operand size override.
+    let immediate_z = if operand_size_override { 2 } else { 4 };
+    let wide_addresses = match mode {
+        Mode::X86 => !address_size_override,
+        Mode::X64 => true,
+    };
+
+    let opcode = *code.get(position)?;
+    position += 1;
+
+    let (map, opcode, vex) = match opcode {
+        // VEX, in 32 bit mode LES and LDS unless the next byte looks like a register operand.
+        0xC4 | 0xC5 if mode == Mode::X64 || *code.get(position)? >= 0xC0 => {
+            let map = match opcode {
+                0xC5 => 1,
+                _ => code[position] & 0x1F,
+            };
+
+            position += if opcode == 0xC5 { 1 } else { 2 };
+
+            let opcode = *code.get(position)?;
+            position += 1;
+
+            (vex_map(map)?, opcode, true)
+        }
+        // EVEX, in 32 bit mode BOUND unless the next byte looks like a register operand.
+        0x62 if mode == Mode::X64 || *code.get(position)? >= 0xC0 => {
+            let map = code[position] & 0x07;
+            position += 3;
+
+            let opcode = *code.get(position)?;
+            position += 1;
+
+            (vex_map(map)?, opcode, true)
+        }
+        0x0F => {
+            let opcode = *code.get(position)?;
+            position += 1;
+
+            match opcode {
+                0x38 | 0x3A => {
+                    let map = match opcode {
+                        0x38 => Map::ThreeByte38,
+                        _ => Map::ThreeByte3A,
+                    };
+
+                    let opcode = *code.get(position)?;
+                    position += 1;
+
+                    (map, opcode, false)
+                }
+                _ => (Map::TwoByte, opcode, false),
+            }
+        }
+        _ => (Map::OneByte, opcode, false),
+    };
+
+    let mut instruction = Instruction::default();
+
+    let has_modrm = match map {
+        Map::OneByte => contains(&MODRM_ONE_BYTE, opcode),
+        // `vzeroupper` and `vzeroall` are the only VEX instructions without one.
+        Map::TwoByte if vex => opcode != 0x77,
+        Map::TwoByte => !contains(&NO_MODRM_TWO_BYTE, opcode),
+        Map::ThreeByte38 | Map::ThreeByte3A => true,
+    };
+
+    let mut reg = 0;
+
+    if has_modrm {
+        let modrm = *code.get(position)?;
+        position += 1;
+
+        let modifier = modrm >> 6;
+        let rm = modrm & 0x07;
+        reg = (modrm >> 3) & 0x07;
+
+        let displacement_size = if modifier == 3 {
+            0
+        } else if wide_addresses {
+            let mut base = rm;
+
+            if rm == 4 {
+                base = *code.get(position)? & 0x07;
+                position += 1;
+            }
+
+            match modifier {
+                0 if rm == 5 => {
+                    instruction.rip_relative = mode == Mode::X64;
+                    instruction.absolute = mode == Mode::X86;
+                    4
+                }
+                0 if rm == 4 && base == 5 => {
+                    instruction.absolute = true;
+                    4
+                }
+                0 => 0,
+                1 => 1,
+                _ => 4,
+            }
+        } else {
+            match modifier {
+                0 if rm == 6 => {
+                    instruction.absolute = true;
+                    2
+                }
+                0 => 0,
+                1 => 1,
+                _ => 2,
+            }
+        };
+
+        if displacement_size > 0 {
+            instruction.displacement = Some(Operand {
+                offset: position,
+                size: displacement_size,
+            });
+            position += displacement_size;
+        }
+    }
+
+    let moffs = match mode {
+        Mode::X64 if !address_size_override => 8,
+        _ if wide_addresses => 4,
+        _ => 2,
+    };
+
+    let (immediate_size, relative_size) = match map {
+        Map::OneByte => match opcode {
+            0x04 | 0x0C | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => (1, 0),
+            0x05 | 0x0D | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => (immediate_z, 0),
+            0x68 | 0x69 | 0x81 | 0xA9 | 0xC7 => (immediate_z, 0),
+            0x6A | 0x6B | 0x80 | 0x82 | 0x83 | 0xA8 | 0xC0 | 0xC1 | 0xC6 | 0xCD => (1, 0),
+            0xB0..=0xB7 | 0xD4 | 0xD5 | 0xE4..=0xE7 => (1, 0),
+            0xB8..=0xBF if rex_w => (8, 0),
+            0xB8..=0xBF => (immediate_z, 0),
+            0xA0..=0xA3 => (moffs, 0),
+            0xC2 | 0xCA => (2, 0),
+            // `enter imm16, imm8`
+            0xC8 => (3, 0),
+            0x9A | 0xEA => (immediate_z + 2, 0),
+            0xF6 if reg < 2 => (1, 0),
+            0xF7 if reg < 2 => (immediate_z, 0),
+            0x70..=0x7F | 0xE0..=0xE3 | 0xEB => (0, 1),
+            0xE8 | 0xE9 if mode == Mode::X64 => (0, 4),
+            0xE8 | 0xE9 => (0, immediate_z),
+            _ => (0, 0),
+        },
+        Map::TwoByte => match opcode {
+            0x80..=0x8F if !vex => (0, if mode == Mode::X64 { 4 } else { immediate_z }),
+            _ if contains(&IMM8_TWO_BYTE, opcode) => (1, 0),
+            _ => (0, 0),
+        },
+        Map::ThreeByte38 => (0, 0),
+        Map::ThreeByte3A => (1, 0),
+    };
+
+    if immediate_size > 0 {
+        instruction.immediate = Some(Operand {
+            offset: position,
+            size: if opcode == 0xC8 && map == Map::OneByte {
+                2
+            } else {
+                immediate_size
+            },
+        });
+    }
+
+    if relative_size > 0 {
+        instruction.relative = Some(Operand {
+            offset: position,
+            size: relative_size,
+        });
+    }
+
+    position += immediate_size + relative_size;
+
+    if position > code.len() {
+        return None;
+    }
+
+    instruction.length = position;
+
+    Some(instruction)
+}
+
+fn vex_map(map: u8) -> Option<Map> {
+    match map {
+        1 => Some(Map::TwoByte),
+        2 => Some(Map::ThreeByte38),
+        3 => Some(Map::ThreeByte3A),
+        // The EVEX only FP16 maps, which like `0F38` always have a ModRM byte and no immediate.
+        5 | 6 => Some(Map::ThreeByte38),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn length(code: &[u8], mode: Mode) -> usize {
+        decode(code, mode).unwrap().length
+    }
+
+    fn operand(offset: usize, size: usize) -> Option<Operand> {
+        Some(Operand { offset, size })
+    }
+
+    #[test]
+    fn prefixes() {
+        // mov ax, 0x1234
+        let mov = decode(&[0x66, 0xB8, 0x34, 0x12], Mode::X86).unwrap();
+        assert_eq!((mov.length, mov.immediate), (4, operand(2, 2)));
+
+        // mov eax, fs:[0x30]
+        let moffs = decode(&[0x64, 0xA1, 0x30, 0, 0, 0], Mode::X86).unwrap();
+        assert_eq!((moffs.length, moffs.immediate), (6, operand(2, 4)));
+
+        // movss xmm0, [disp32]
+        let movss = decode(&[0xF3, 0x0F, 0x10, 0x05, 0, 0, 0, 0], Mode::X86).unwrap();
+        assert_eq!((movss.length, movss.displacement), (8, operand(4, 4)));
+        assert!(movss.absolute);
+
+        assert_eq!(decode(&[0x66; 16], Mode::X86), None);
+    }
+
+    #[test]
+    fn modrm_forms() {
+        for mode in [Mode::X86, Mode::X64] {
+            // mov eax, ecx
+            assert_eq!(decode(&[0x8B, 0xC1], mode).unwrap().displacement, None);
+            // mov eax, [ecx]
+            assert_eq!(length(&[0x8B, 0x01], mode), 2);
+            // mov eax, [ecx + 8]
+            let disp8 = decode(&[0x8B, 0x41, 0x08], mode).unwrap();
+            assert_eq!((disp8.length, disp8.displacement), (3, operand(2, 1)));
+            // mov eax, [ecx + 0x100]
+            let disp32 = decode(&[0x8B, 0x81, 0, 1, 0, 0], mode).unwrap();
+            assert_eq!((disp32.length, disp32.displacement), (6, operand(2, 4)));
+            // mov eax, [esp]
+            assert_eq!(length(&[0x8B, 0x04, 0x24], mode), 3);
+            // mov eax, [esp + 4]
+            let sib = decode(&[0x8B, 0x44, 0x24, 0x04], mode).unwrap();
+            assert_eq!((sib.length, sib.displacement), (4, operand(3, 1)));
+            // mov eax, [disp32] through a SIB byte without a base
+            let absolute = decode(&[0x8B, 0x04, 0x25, 0, 0, 0, 0], mode).unwrap();
+            assert_eq!((absolute.length, absolute.displacement), (7, operand(3, 4)));
+            assert!(absolute.absolute && !absolute.rip_relative);
+        }
+
+        // mov eax, [disp32] is absolute in 32 bit and rip relative in 64 bit mode.
+        let x86 = decode(&[0x8B, 0x05, 0, 0, 0, 0], Mode::X86).unwrap();
+        let x64 = decode(&[0x8B, 0x05, 0, 0, 0, 0], Mode::X64).unwrap();
+        assert!(x86.absolute && !x86.rip_relative);
+        assert!(x64.rip_relative && !x64.absolute);
+
+        // mov eax, [disp16] with 16 bit addresses
+        let disp16 = decode(&[0x67, 0x8B, 0x06, 0x34, 0x12], Mode::X86).unwrap();
+        assert_eq!((disp16.length, disp16.displacement), (5, operand(3, 2)));
+
+        // test ecx, imm32 and not ecx share their opcode
+        assert_eq!(length(&[0xF7, 0xC1, 0x78, 0x56, 0x34, 0x12], Mode::X86), 6);
+        assert_eq!(length(&[0xF7, 0xD1], Mode::X86), 2);
+    }
+
+    #[test]
+    fn rex() {
+        // mov rax, imm64
+        let mov = decode(&[0x48, 0xB8, 1, 2, 3, 4, 5, 6, 7, 8], Mode::X64).unwrap();
+        assert_eq!((mov.length, mov.immediate), (10, operand(2, 8)));
+        // mov rax, [moffs64]
+        assert_eq!(length(&[0x48, 0xA1, 1, 2, 3, 4, 5, 6, 7, 8], Mode::X64), 10);
+        // call r8
+        assert_eq!(length(&[0x41, 0xFF, 0xD0], Mode::X64), 3);
+        // lea r8, [rip + disp32]
+        let lea = decode(&[0x4C, 0x8D, 0x05, 0, 0, 0, 0], Mode::X64).unwrap();
+        assert_eq!((lea.length, lea.displacement), (7, operand(3, 4)));
+        // dec eax in 32 bit mode
+        assert_eq!(length(&[0x48, 0x8B, 0xC1], Mode::X86), 1);
+    }
+
+    #[test]
+    fn opcode_maps() {
+        // syscall
+        assert_eq!(length(&[0x0F, 0x05], Mode::X64), 2);
+        // movups xmm0, xmm1
+        assert_eq!(length(&[0x0F, 0x10, 0xC1], Mode::X86), 3);
+        // nop dword [eax + eax]
+        assert_eq!(length(&[0x0F, 0x1F, 0x44, 0x00, 0x00], Mode::X86), 5);
+        // bt eax, 5
+        let bt = decode(&[0x0F, 0xBA, 0xE0, 0x05], Mode::X86).unwrap();
+        assert_eq!((bt.length, bt.immediate), (4, operand(3, 1)));
+        // pshufb xmm0, xmm1
+        assert_eq!(length(&[0x66, 0x0F, 0x38, 0x00, 0xC1], Mode::X86), 5);
+        // palignr xmm0, xmm1, 8
+        let palignr = decode(&[0x66, 0x0F, 0x3A, 0x0F, 0xC1, 0x08], Mode::X86).unwrap();
+        assert_eq!((palignr.length, palignr.immediate), (6, operand(5, 1)));
+        // vzeroupper
+        assert_eq!(length(&[0xC5, 0xF8, 0x77], Mode::X64), 3);
+        // vmovss xmm0, [rip + disp32]
+        let vmovss = decode(&[0xC5, 0xFA, 0x10, 0x05, 0, 0, 0, 0], Mode::X64).unwrap();
+        assert_eq!((vmovss.length, vmovss.displacement), (8, operand(4, 4)));
+        assert!(vmovss.rip_relative);
+    }
+
+    #[test]
+    fn branches() {
+        // jmp $
+        let jmp = decode(&[0xEB, 0xFE], Mode::X86).unwrap();
+        assert_eq!((jmp.length, jmp.relative), (2, operand(1, 1)));
+        // jz rel32
+        let jz = decode(&[0x0F, 0x84, 0, 0, 0, 0], Mode::X86).unwrap();
+        assert_eq!((jz.length, jz.relative), (6, operand(2, 4)));
+        // call rel32, even with an operand size override in 64 bit mode
+        assert_eq!(length(&[0xE8, 0, 0, 0, 0], Mode::X64), 5);
+        assert_eq!(length(&[0x66, 0xE8, 0, 0, 0, 0], Mode::X64), 6);
+        // call rel16
+        let call = decode(&[0x66, 0xE8, 0, 0], Mode::X86).unwrap();
+        assert_eq!((call.length, call.relative), (4, operand(2, 2)));
+        // ret imm16 and enter imm16, imm8
+        assert_eq!(length(&[0xC2, 0x08, 0x00], Mode::X86), 3);
+        let enter = decode(&[0xC8, 0x10, 0x00, 0x00], Mode::X86).unwrap();
+        assert_eq!((enter.length, enter.immediate), (4, operand(1, 2)));
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(decode(&[], Mode::X86), None);
+        assert_eq!(decode(&[0x8B], Mode::X86), None);
+        assert_eq!(decode(&[0x8B, 0x44, 0x24], Mode::X86), None);
+        assert_eq!(decode(&[0xE8, 0, 0], Mode::X86), None);
+        assert_eq!(decode(&[0x48], Mode::X64), None);
+    }
+}
@@ -0,0 +1,511 @@
+//! Finding functions by the strings they reference.
+//!
+//! Strings survive game updates much better than the code around them, so a function that
+//! is the only one using a string can be found without a signature: the string is located in
+//! the read-only data, the code is searched for instructions referencing it, absolute `imm32`
+//! and `disp32` operands on x86 and `rip` relative displacements on x86-64, and the start of
+//! the function around each reference is found by walking back to padding, a prologue or the
+//! target of a call.
+//! # Examples
+//! ```no_run
+//! use sdk::utils::platform::modules;
+//! use sdk::utils::xref::xref_scan;
+//!
+//! let function = xref_scan(modules::CLIENT, "CHudWeaponSelection::SelectWeaponSlot")?;
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use libc::c_char;
+
+use crate::utils::error::Error;
+use crate::utils::signature::Signature;
+use crate::utils::signature_gen::Code;
+use crate::utils::x86::{decode, Instruction, Mode};
+
+/// Functions longer than this are not searched for their start.
+const MAX_FUNCTION_SIZE: usize = 0x4000;
+
+/// Instructions compilers start functions with.
+const PROLOGUES_X86: &[&[u8]] = &[
+    &[0x55, 0x8B, 0xEC], // push ebp; mov ebp, esp
+    &[0x55, 0x89, 0xE5], // push ebp; mov ebp, esp
+];
+
+const PROLOGUES_X64: &[&[u8]] = &[
+    &[0xF3, 0x0F, 0x1E, 0xFA], // endbr64
+    &[0x55, 0x48, 0x89, 0xE5], // push rbp; mov rbp, rsp
+    &[0x48, 0x89, 0x5C, 0x24], // mov [rsp + disp8], rbx
+    &[0x48, 0x83, 0xEC],       // sub rsp, imm8
+    &[0x48, 0x81, 0xEC],       // sub rsp, imm32
+    &[0x40, 0x53],             // push rbx
+    &[0x41, 0x57],             // push r15
+    &[0x41, 0x56],             // push r14
+    &[0x41, 0x55],             // push r13
+    &[0x41, 0x54],             // push r12
+];
+
+/// The encodings compilers pad with between functions, besides `int3`.
+const NOPS: &[&[u8]] = &[
+    &[0x90],                                                       // nop
+    &[0x66, 0x90],                                                 // xchg ax, ax
+    &[0x0F, 0x1F, 0x00],                                           // nop [eax]
+    &[0x0F, 0x1F, 0x40, 0x00],                                     // nop [eax + 0]
+    &[0x0F, 0x1F, 0x44, 0x00, 0x00],                               // nop [eax + eax + 0]
+    &[0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00],                         // nop [eax + eax + 0]
+    &[0x0F, 0x1F, 0x80, 0x00, 0x00, 0x00, 0x00],                   // nop [eax + 0]
+    &[0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],             // nop [eax + eax + 0]
+    &[0x66, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00],       // nop [eax + eax + 0]
+    &[0x66, 0x2E, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00], // nop cs:[eax + eax + 0]
+    &[0x89, 0xF6],                                                 // mov esi, esi
+    &[0x8D, 0x76, 0x00],                                           // lea esi, [esi + 0]
+    &[0x8D, 0x74, 0x26, 0x00],                                     // lea esi, [esi + 0]
+    &[0x8D, 0xB6, 0x00, 0x00, 0x00, 0x00],                         // lea esi, [esi + 0]
+    &[0x8D, 0xB4, 0x26, 0x00, 0x00, 0x00, 0x00],                   // lea esi, [esi + 0]
+    &[0x8D, 0xBC, 0x27, 0x00, 0x00, 0x00, 0x00],                   // lea edi, [edi + 0]
+];
+
+/// `endbr64` and `endbr32`, which can come before a prologue.
+const END_BRANCHES: &[&[u8]] = &[&[0xF3, 0x0F, 0x1E, 0xFA], &[0xF3, 0x0F, 0x1E, 0xFB]];
+
+/// An instruction referencing an address.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Xref {
+    /// The referencing instruction.
+    pub address: usize,
+    /// The start of the function the instruction is in, if it could be found.
+    pub function: Option<usize>,
+}
+
+/// Looks up references in the code of a module.
+///
+/// Creating it decodes all the code once to find the targets of direct calls, which are
+/// known function starts, and the returns and jumps backwards, after which functions end.
+pub struct XrefIndex<'c, 'a> {
+    code: &'c Code<'a>,
+    calls: BTreeSet<usize>,
+    /// The addresses right after a `ret` or `ret imm16`.
+    returns: BTreeSet<usize>,
+    /// The addresses right after a `jmp` to a lower address.
+    jumps_back: BTreeSet<usize>,
+}
+
+impl<'c, 'a> XrefIndex<'c, 'a> {
+    pub fn new(code: &'c Code<'a>) -> Self {
+        let mut calls = BTreeSet::new();
+        let mut returns = BTreeSet::new();
+        let mut jumps_back = BTreeSet::new();
+
+        sweep(code, |address, bytes, instruction| {
+            let end = address + instruction.length;
+
+            if is_return(&bytes[..instruction.length], instruction, code.mode) {
+                returns.insert(end);
+            }
+
+            if let Some(relative) = instruction.relative {
+                let branch = target(address, bytes, instruction, relative.offset);
+
+                match bytes[relative.offset - 1] {
+                    0xE8 if relative.size == 4 => {
+                        calls.insert(branch);
+                    }
+                    0xE9 | 0xEB if branch < end => {
+                        jumps_back.insert(end);
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        Self {
+            code,
+            calls,
+            returns,
+            jumps_back,
+        }
+    }
+
+    /// Returns the address of every copy of `string` in the read-only data.
+    ///
+    /// Executable sections are searched as well, ELF objects linked without
+    /// `-z separate-code` map their read-only data into the executable segment.
+    /// Only whole strings are found, the match has to be followed by a `NUL`.
+    pub fn strings(&self, string: &str) -> Vec<usize> {
+        let bytes = [string.as_bytes(), &[0]].concat();
+        let mask = vec![true; bytes.len()];
+        let signature = match Signature::new(bytes, mask) {
+            Ok(signature) => signature,
+            Err(_) => return Vec::new(),
+        };
+
+        self.code
+            .data
+            .iter()
+            .chain(&self.code.sections)
+            .flat_map(|(start, data)| signature.find_iter(data).map(move |offset| start + offset))
+            .collect()
+    }
+
+    /// Returns the address of every instruction that references `address`.
+    pub fn references(&self, address: usize) -> Vec<usize> {
+        let absolute = (address + self.code.image_base) as u64;
+        let mut references = Vec::new();
+
+        sweep(self.code, |instruction_address, bytes, instruction| {
+            let referenced = match self.code.mode {
+                Mode::X64 => {
+                    instruction.rip_relative
+                        && instruction.displacement.is_some_and(|displacement| {
+                            target(instruction_address, bytes, instruction, displacement.offset)
+                                == address
+                        })
+                }
+                Mode::X86 => [instruction.displacement, instruction.immediate]
+                    .iter()
+                    .flatten()
+                    .filter(|operand| operand.size == 4)
+                    .any(|operand| read_u32(bytes, operand.offset) as u64 == absolute),
+            };
+
+            if referenced {
+                references.push(instruction_address);
+            }
+        });
+
+        references
+    }
+
+    /// Returns the start of the function `address` is in.
+    ///
+    /// Walks back from `address` to the closest call target, 16 byte aligned address after
+    /// padding, or prologue that is aligned or follows padding or a `ret`, from which decoding
+    /// the instructions lands on `address` without passing `int3`. Functions in code that is
+    /// neither aligned nor uses prologues are only found if they are called directly.
+    pub fn function_start(&self, address: usize) -> Option<usize> {
+        let (start, code) = self
+            .code
+            .sections
+            .iter()
+            .find(|(start, code)| address >= *start && address < start + code.len())?;
+
+        let prologues = match self.code.mode {
+            Mode::X86 => PROLOGUES_X86,
+            Mode::X64 => PROLOGUES_X64,
+        };
+
+        let offset = address - start;
+
+        (offset.saturating_sub(MAX_FUNCTION_SIZE)..=offset)
+            .rev()
+            .find(|candidate| {
+                let aligned = (start + candidate) % 16 == 0;
+                // Compilers also align blocks inside of functions with nops, but not right
+                // after a `ret` or a jump back.
+                let padding = padding_start(&code[..*candidate]);
+                let padded = *candidate == 0
+                    || code[candidate - 1] == 0xCC
+                    || padding < *candidate && self.ends_function(start + padding);
+                // A prologue right after a push or another prologue is part of a longer one.
+                let continued = matches!(code[..*candidate].last(), Some(0x50..=0x57))
+                    || END_BRANCHES
+                        .iter()
+                        .chain(prologues)
+                        .any(|before| code[..*candidate].ends_with(before));
+                let prologue = !continued
+                    && prologues
+                        .iter()
+                        .any(|prologue| code[*candidate..].starts_with(prologue));
+
+                // Only setting up a frame pointer is rare enough inside of functions to be
+                // trusted right after a `ret`, without alignment.
+                let after_return =
+                    self.returns.contains(&(start + candidate)) && code[*candidate] == 0x55;
+
+                (self.calls.contains(&(start + candidate))
+                    || aligned && padded
+                    || prologue && (aligned || padded || after_return))
+                    && reaches(&code[*candidate..], offset - candidate, self.code.mode)
+            })
+            .map(|candidate| start + candidate)
+    }
+
+    /// Returns `true` if a return or a jump backwards, after which a function can't
+    /// continue, ends at `address`.
+    fn ends_function(&self, address: usize) -> bool {
+        self.returns.contains(&address) || self.jumps_back.contains(&address)
+    }
+
+    /// Returns every instruction referencing a copy of `string` and the function it is in.
+    pub fn string_xrefs(&self, string: &str) -> Vec<Xref> {
+        self.strings(string)
+            .into_iter()
+            .flat_map(|address| self.references(address))
+            .map(|address| Xref {
+                address,
+                function: self.function_start(address),
+            })
+            .collect()
+    }
+}
+
+/// Calls `visit` with the address, the code from there on and the layout of every
+/// instruction in the executable sections, skipping bytes that don't decode.
+fn sweep<F: FnMut(usize, &[u8], &Instruction)>(code: &Code, mut visit: F) {
+    for (start, bytes) in &code.sections {
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            match decode(&bytes[offset..], code.mode) {
+                Some(instruction) => {
+                    visit(start + offset, &bytes[offset..], &instruction);
+                    offset += instruction.length;
+                }
+                None => offset += 1,
+            }
+        }
+    }
+}
+
+/// Returns the target of the 32 bit relative operand at `offset`.
+fn target(address: usize, bytes: &[u8], instruction: &Instruction, offset: usize) -> usize {
+    (address + instruction.length).wrapping_add(read_u32(bytes, offset) as i32 as isize as usize)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut value = [0; 4];
+    value.copy_from_slice(&bytes[offset..offset + 4]);
+
+    u32::from_le_bytes(value)
+}
+
+/// Returns where the `int3` and nop padding at the end of `code` starts.
+fn padding_start(code: &[u8]) -> usize {
+    let mut end = code.len();
+
+    loop {
+        let code = &code[..end];
+
+        let nop = NOPS
+            .iter()
+            .filter(|nop| code.ends_with(nop))
+            .max_by_key(|nop| nop.len());
+
+        end -= match nop {
+            _ if code.ends_with(&[0xCC]) => 1,
+            Some(nop) => nop.len(),
+            None => return end,
+        };
+    }
+}
+
+/// Returns `true` if `bytes` is a `ret` or `ret imm16`, possibly with prefixes.
+fn is_return(bytes: &[u8], instruction: &Instruction, mode: Mode) -> bool {
+    if instruction.displacement.is_some() || instruction.relative.is_some() {
+        return false;
+    }
+
+    let opcode = match instruction.immediate {
+        None => bytes.len() - 1,
+        Some(immediate) if immediate.size == 2 => immediate.offset - 1,
+        Some(_) => return false,
+    };
+    let expected = if instruction.immediate.is_some() {
+        0xC2
+    } else {
+        0xC3
+    };
+
+    // Anything else before the opcode, e.g. `8B` in `mov eax, ebx`, makes it a ModRM byte.
+    bytes[opcode] == expected
+        && bytes[..opcode].iter().all(|byte| match byte {
+            0x26 | 0x2E | 0x36 | 0x3E | 0x64 | 0x65 | 0x66 | 0x67 | 0xF0 | 0xF2 | 0xF3 => true,
+            0x40..=0x4F => mode == Mode::X64,
+            _ => false,
+        })
+}
+
+/// Returns `true` if decoding `code` has an instruction start at `offset`, without any
+/// `int3` in between.
+fn reaches(code: &[u8], offset: usize, mode: Mode) -> bool {
+    let mut position = 0;
+
+    while position < offset {
+        if code[position] == 0xCC {
+            return false;
+        }
+
+        match decode(&code[position..], mode) {
+            Some(instruction) => position += instruction.length,
+            None => return false,
+        }
+    }
+
+    position == offset
+}
+
+/// Returns the start of the only function in the given loaded module that references
+/// `string`.
+///
+/// Fails if the string isn't referenced, if the references are in more than one function,
+/// or if the function of any reference can't be found, since it could be another one.
+pub fn xref_scan(module_name: *const c_char, string: &str) -> Result<usize, Error> {
+    let code = Code::from_module(module_name);
+
+    only_function(string, &XrefIndex::new(&code).string_xrefs(string))
+}
+
+/// Returns the function all of `xrefs` to `string` are in.
+fn only_function(string: &str, xrefs: &[Xref]) -> Result<usize, Error> {
+    let unresolved = xrefs
+        .iter()
+        .filter(|xref| xref.function.is_none())
+        .map(|xref| format!("{:#X}", xref.address))
+        .collect::<Vec<_>>();
+
+    if !unresolved.is_empty() {
+        return Err(Error::NotFound {
+            item: format!(
+                "the function referencing \"{}\" at {}",
+                string,
+                unresolved.join(", ")
+            ),
+        });
+    }
+
+    let functions = xrefs
+        .iter()
+        .filter_map(|xref| xref.function)
+        .collect::<BTreeSet<_>>();
+
+    match functions.into_iter().collect::<Vec<_>>().as_slice() {
+        [function] => Ok(*function),
+        [] => Err(Error::NotFound {
+            item: format!("a reference to \"{}\"", string),
+        }),
+        functions => Err(Error::Other {
+            msg: format!(
+                "\"{}\" is referenced by {} functions",
+                string,
+                functions.len()
+            ),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::image::{Format, Image, ImageSection};
+
+    fn section(rva: usize, executable: bool, data: &[u8]) -> ImageSection<'_> {
+        ImageSection {
+            name: if executable { ".text" } else { ".rodata" }.into(),
+            rva,
+            size: data.len(),
+            executable,
+            writable: false,
+            data,
+        }
+    }
+
+    fn image<'a>(format: Format, mode: Mode, sections: Vec<ImageSection<'a>>) -> Image<'a> {
+        Image {
+            format,
+            mode,
+            image_base: if mode == Mode::X86 { 0x1000_0000 } else { 0 },
+            sections,
+            relocations: Vec::new(),
+        }
+    }
+
+    fn xrefs(code: &Code, string: &str) -> Vec<(usize, Option<usize>)> {
+        XrefIndex::new(code)
+            .string_xrefs(string)
+            .into_iter()
+            .map(|xref| (xref.address, xref.function))
+            .collect()
+    }
+
+    #[test]
+    fn x86_functions() {
+        let text = [
+            0x55, 0x8B, 0xEC, // push ebp; mov ebp, esp
+            0x8B, 0xC3, // mov eax, ebx, which ends like a `ret`
+            0x55, 0x8B, 0xEC, // push ebp; mov ebp, esp
+            0x68, 0x00, 0x20, 0x00, 0x10, // push "weapon_knife"
+            0x5D, 0xC2, 0x08, 0x00, // pop ebp; ret 8
+            0x55, 0x8B, 0xEC, // push ebp; mov ebp, esp
+            0x68, 0x00, 0x20, 0x00, 0x10, // push "weapon_knife"
+            0x5D, 0xC3, // pop ebp; ret
+            0xCC, 0xCC, 0xCC, 0xCC, 0xCC,
+        ];
+        let image = image(
+            Format::Pe,
+            Mode::X86,
+            vec![
+                section(0x1000, true, &text),
+                section(0x2000, false, b"weapon_knife\0"),
+            ],
+        );
+        let code = Code::from_image(&image);
+        let index = XrefIndex::new(&code);
+
+        assert_eq!(index.strings("weapon_knife"), vec![0x2000]);
+        assert_eq!(index.references(0x2000), vec![0x1008, 0x1014]);
+        assert_eq!(
+            xrefs(&code, "weapon_knife"),
+            vec![(0x1008, Some(0x1000)), (0x1014, Some(0x1011))]
+        );
+    }
+
+    #[test]
+    fn x64_functions() {
+        let mut text = vec![
+            0x55, 0x48, 0x89, 0xE5, // push rbp; mov rbp, rsp
+            0x48, 0x89, 0xC3, // mov rbx, rax, which ends like a `ret`
+            0x55, 0x48, 0x89, 0xE5, // push rbp; mov rbp, rsp
+            0x48, 0x8D, 0x05, 0x1E, 0x00, 0x00, 0x00, // lea rax, [rip + "weapon_knife"]
+            0x5D, 0xC3, // pop rbp; ret
+            0x55, 0x48, 0x89, 0xE5, // push rbp; mov rbp, rsp
+            0x48, 0x8D, 0x05, 0x11, 0x00, 0x00, 0x00, // lea rax, [rip + "weapon_knife"]
+            0x5D, 0xC3, // pop rbp; ret
+        ];
+        text.resize(0x30, 0xCC);
+        // Without `-z separate-code` the read-only data shares the executable segment.
+        text.extend_from_slice(b"weapon_knife\0");
+
+        let image = image(Format::Elf, Mode::X64, vec![section(0x1000, true, &text)]);
+        let code = Code::from_image(&image);
+        let index = XrefIndex::new(&code);
+
+        assert_eq!(index.strings("weapon_knife"), vec![0x1030]);
+        assert_eq!(index.references(0x1030), vec![0x100B, 0x1018]);
+        assert_eq!(
+            xrefs(&code, "weapon_knife"),
+            vec![(0x100B, Some(0x1000)), (0x1018, Some(0x1014))]
+        );
+    }
+
+    #[test]
+    fn unresolved_references() {
+        let xref = |address, function| Xref { address, function };
+
+        assert_eq!(
+            only_function(
+                "weapon_knife",
+                &[xref(0x1008, Some(0x1000)), xref(0x100C, Some(0x1000))]
+            )
+            .ok(),
+            Some(0x1000)
+        );
+        assert!(only_function(
+            "weapon_knife",
+            &[xref(0x1008, Some(0x1000)), xref(0x1208, None)]
+        )
+        .is_err());
+        assert!(only_function("weapon_knife", &[]).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+//! The game build the SDK runs against, read from `csgo/steam.inf`.
+//!
+//! `sdk::initialize` detects it to pick signature variants, it is also meant to key
+//! caches that are only valid for a single build.
+//! # Examples
+//! ```no_run
+//! use sdk::netvar::dump::NetvarDump;
+//! use sdk::version::GameVersion;
+//!
+//! let version = GameVersion::detect()?;
+//!
+//! NetvarDump::capture().save(format!("netvars-{}.txt", version.cache_key()))?;
+//! # Ok::<(), sdk::utils::error::Error>(())
+//! ```
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use crate::utils::error::Error;
+
+/// The versions `steam.inf` lists.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GameVersion {
+    /// The version shown to players, e.g. `1.38.8.1`.
+    pub patch_version: String,
+    pub client_version: u32,
+    pub server_version: u32,
+    /// The revision of the engine source the build was made from.
+    pub source_revision: u32,
+}
+
+impl GameVersion {
+    /// Parses the `key=value` lines of a `steam.inf` file, unknown keys are ignored.
+    /// # Examples
+    /// ```
+    /// use sdk::version::GameVersion;
+    ///
+    /// let version = GameVersion::parse(
+    ///     "ClientVersion=1575\nServerVersion=1575\nPatchVersion=1.38.8.1\nSourceRevision=8128170",
+    /// )?;
+    ///
+    /// assert_eq!(version.build(), 1575);
+    /// assert_eq!(version.cache_key(), "1575-8128170");
+    /// assert!(GameVersion::parse("PatchVersion=1.38.8.1").is_err());
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let value = |key: &str| {
+            text.lines()
+                .filter_map(|line| line.split_once('='))
+                .find(|(name, _)| name.trim() == key)
+                .map(|(_, value)| value.trim())
+                .ok_or_else(|| Error::NotFound {
+                    item: format!("{} in steam.inf", key),
+                })
+        };
+
+        let number = |key: &str| {
+            value(key)?.parse().map_err(|_| Error::Other {
+                msg: format!("{} in steam.inf is not a number", key),
+            })
+        };
+
+        Ok(Self {
+            patch_version: value("PatchVersion")?.to_string(),
+            client_version: number("ClientVersion")?,
+            server_version: number("ServerVersion")?,
+            source_revision: number("SourceRevision")?,
+        })
+    }
+
+    /// The build signature variants are picked by, which is the client version.
+    pub fn build(&self) -> u32 {
+        self.client_version
+    }
+
+    /// A key that is different for every build, e.g. to name cache files.
+    pub fn cache_key(&self) -> String {
+        format!("{}-{}", self.client_version, self.source_revision)
+    }
+
+    /// Reads the `steam.inf` file at `path`.
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|error| Error::Other {
+            msg: error.to_string(),
+        })?;
+
+        Self::parse(&text)
+    }
+
+    /// Returns the `steam.inf` in the closest directory above `module_path`, which is
+    /// `csgo/steam.inf` for the game's modules.
+    /// # Examples
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use sdk::version::GameVersion;
+    ///
+    /// let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+    /// let path = GameVersion::locate(fixtures.join("csgo/bin/linux64/client_client.so"))?;
+    ///
+    /// assert_eq!(path, fixtures.join("csgo/steam.inf"));
+    /// assert_eq!(GameVersion::load(path)?.patch_version, "1.38.8.1");
+    /// # Ok::<(), sdk::utils::error::Error>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn locate<P: AsRef<std::path::Path>>(module_path: P) -> Result<std::path::PathBuf, Error> {
+        module_path
+            .as_ref()
+            .ancestors()
+            .skip(1)
+            .map(|directory| directory.join("steam.inf"))
+            .find(|path| path.is_file())
+            .ok_or_else(|| Error::NotFound {
+                item: format!("steam.inf above {}", module_path.as_ref().display()),
+            })
+    }
+
+    /// Reads the `steam.inf` of the game the client module was loaded from.
+    #[cfg(feature = "std")]
+    pub fn detect() -> Result<Self, Error> {
+        use crate::utils::memory::module_path;
+        use crate::utils::platform::modules;
+
+        let module_path = module_path(modules::CLIENT).ok_or_else(|| Error::NotFound {
+            item: obfstr!("client module").into(),
+        })?;
+
+        Self::load(Self::locate(module_path)?)
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (client {}, server {}, revision {})",
+            self.patch_version, self.client_version, self.server_version, self.source_revision
+        )
+    }
+}
+
+/// Same as [`GameVersion::detect`], `None` without the `std` feature.
+pub(crate) fn detect() -> Option<GameVersion> {
+    #[cfg(feature = "std")]
+    return GameVersion::detect().ok();
+
+    #[cfg(not(feature = "std"))]
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEAM_INF: &str = "ClientVersion=1575\r\nServerVersion=1575\r\nPatchVersion=1.38.8.1\r\nProductName=csgo\r\nSourceRevision=8128170\r\n";
+
+    #[test]
+    fn parse() {
+        let version = GameVersion::parse(STEAM_INF).unwrap();
+
+        assert_eq!(version.patch_version, "1.38.8.1");
+        assert_eq!(version.client_version, 1575);
+        assert_eq!(version.server_version, 1575);
+        assert_eq!(version.source_revision, 8128170);
+    }
+
+    #[test]
+    fn non_numeric_value() {
+        let text = STEAM_INF.replace("ServerVersion=1575", "ServerVersion=15a75");
+
+        match GameVersion::parse(&text) {
+            Err(Error::Other { msg }) => {
+                assert_eq!(msg, "ServerVersion in steam.inf is not a number")
+            }
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+
+    #[test]
+    fn missing_key() {
+        let text = STEAM_INF.replace("SourceRevision", "Revision");
+
+        match GameVersion::parse(&text) {
+            Err(Error::NotFound { item }) => assert_eq!(item, "SourceRevision in steam.inf"),
+            result => panic!("unexpected {:?}", result),
+        }
+
+        assert!(GameVersion::parse("").is_err());
+    }
+}